@@ -0,0 +1,513 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::data::Data;
+use crate::error::{ErrorKind, ExecResult, RuntimeError};
+use crate::format::{format_date, format_string};
+use crate::runtime::{RunTime, Signal};
+use crate::step::exit_code;
+use crate::streams::Received;
+
+//a function implemented in Rust, called like any other function. Takes its arguments from P0, P1... and returns in RET0, RET1...
+#[derive(Clone)]
+pub(crate) struct NativeFunction(pub(crate) Rc<dyn Fn(&mut RunTime) -> ExecResult>);
+
+impl std::fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native>")
+    }
+}
+
+type Builtin = fn(&mut RunTime) -> ExecResult;
+
+//registered as native functions on every new RunTime
+pub(crate) const BUILTIN_FUNCTIONS: [(&str, Builtin); 31] = [
+    ("debug", RunTime::debug), ("print", RunTime::print), ("printline", RunTime::printline), ("input", RunTime::input), ("exit", RunTime::exit), ("atexit", RunTime::atexit),
+    ("input_int", RunTime::input_int), ("input_float", RunTime::input_float), ("input_bool", RunTime::input_bool), ("input_char", RunTime::input_char), ("read_all", RunTime::read_all),
+    ("printf", RunTime::printf), ("format", RunTime::format),
+    ("file_open", RunTime::file_open), ("file_read_line", RunTime::file_read_line), ("file_read_all", RunTime::file_read_all), ("file_write", RunTime::file_write), ("file_append", RunTime::file_append),
+    ("file_close", RunTime::file_close), ("file_exists", RunTime::file_exists), ("file_delete", RunTime::file_delete), ("dir_list", RunTime::dir_list),
+    ("getenv", RunTime::getenv), ("setenv", RunTime::setenv), ("time", RunTime::time), ("date", RunTime::date),
+    ("clock", RunTime::clock), ("sleep", RunTime::sleep), ("cwd", RunTime::cwd), ("exec", RunTime::exec),
+    ("checkpoint", RunTime::checkpoint),
+];
+
+#[derive(Debug)]
+pub(crate) enum OpenFile {
+    Read(BufReader<File>),
+    Write(File),
+}
+
+impl RunTime {
+    //writes text to the output stream and flushes it so prompts show up before input is read
+    fn write_output(&mut self, instruction: &str, text: &str) -> ExecResult {
+        self.streams.output.write_all(text.as_bytes())
+            .and_then(|_| self.streams.output.flush())
+            .map_err(|error| RunTime::io_error(instruction, "output", error))
+    }
+
+    fn debug(&mut self) -> ExecResult {
+        let state = format!("{:#?}\n", self);
+        self.write_output("debug", &state)
+    }
+
+    fn print(&mut self) -> ExecResult {
+        let text = self.registers.get("P0").unwrap().to_string();
+        self.write_output("print", &text)
+    }
+
+    fn printline(&mut self) -> ExecResult {
+        let text = self.registers.get("P0").unwrap().to_string() + "\n";
+        self.write_output("printline", &text)
+    }
+
+    //reads one line of the input stream, None at the end of the input. Sets the eof flag and clears the error flag
+    fn read_input_line(&mut self, instruction: &str) -> Result<Option<String>, RuntimeError> {
+        let input = self.read_input(instruction, false)?;
+        self.eof_flag = input.is_empty();
+        self.error_flag = false;
+        Ok(if input.is_empty() { None } else { Some(input.trim().to_string()) })
+    }
+
+    //a line, or everything for whole, of the input stream within the time and value size limits
+    fn read_input(&mut self, instruction: &str, whole: bool) -> Result<String, RuntimeError> {
+        let (max, deadline) = (self.max_value_size(), self.deadline);
+        match self.streams.input.read(whole, max, deadline).map_err(|error| RunTime::io_error(instruction, "input", error))? {
+            Received::Done(text) => Ok(text),
+            Received::TooLarge => Err(self.value_too_large(instruction)),
+            Received::OutOfTime => Err(self.out_of_time()),
+        }
+    }
+
+    //RET0 = the line typed, null at the end of the input
+    //with input fed by the host, true when there is no complete line yet (or for whole, the input is not closed yet).
+    //the instruction is executed again on the next step, after the host fed more input
+    fn wait_for_input(&mut self, whole: bool) -> bool {
+        self.waiting_for_input = !self.streams.input.ready(whole);
+        self.waiting_for_input
+    }
+
+    fn input(&mut self) -> ExecResult {
+        if self.wait_for_input(false) {
+            return Ok(());
+        }
+        let line = self.read_input_line("input")?;
+        self.registers.insert("RET0".to_string(), line.map_or(Data::Null, Data::String));
+        Ok(())
+    }
+
+    //prints P0 if it holds a string and clears it, so a later call does not show the same prompt again.
+    //then reads a line and converts it with parse.
+    //RET0 is null and the eof or error flag is set if there was nothing to read or it could not be converted
+    fn typed_input(&mut self, instruction: &str, parse: fn(&str) -> Option<Data>) -> ExecResult {
+        if let Some(Data::String(prompt)) = self.registers.get("P0") {
+            let prompt = prompt.to_string();
+            self.registers.insert("P0".to_string(), Data::Null);
+            self.write_output(instruction, &prompt)?;
+        }
+        if self.wait_for_input(false) {
+            return Ok(());
+        }
+        let value = match self.read_input_line(instruction)? {
+            Some(line) => parse(&line),
+            None => None,
+        };
+        self.error_flag = value.is_none() && !self.eof_flag;
+        self.registers.insert("RET0".to_string(), value.unwrap_or(Data::Null));
+        Ok(())
+    }
+
+    fn input_int(&mut self) -> ExecResult {
+        self.typed_input("input_int", |line| line.parse::<i64>().ok().map(Data::Int))
+    }
+
+    fn input_float(&mut self) -> ExecResult {
+        self.typed_input("input_float", |line| line.parse::<f64>().ok().map(Data::Float))
+    }
+
+    fn input_bool(&mut self) -> ExecResult {
+        self.typed_input("input_bool", |line| match line.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Some(Data::Bool(true)),
+            "false" | "no" | "n" | "0" => Some(Data::Bool(false)),
+            _ => None,
+        })
+    }
+
+    fn input_char(&mut self) -> ExecResult {
+        self.typed_input("input_char", |line| {
+            let mut chars = line.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(Data::String(c.to_string())),
+                _ => None,
+            }
+        })
+    }
+
+    //RET0 = everything left on the input stream, sets the eof flag
+    fn read_all(&mut self) -> ExecResult {
+        if self.wait_for_input(true) {
+            return Ok(());
+        }
+        let contents = self.read_input("read_all", true)?;
+        self.eof_flag = true;
+        self.error_flag = false;
+        self.registers.insert("RET0".to_string(), Data::String(contents));
+        Ok(())
+    }
+
+    //formats P0 with P1, P2... as the arguments
+    fn format_params(&self, instruction: &str) -> Result<String, RuntimeError> {
+        let format = self.string_param("P0", instruction)?.replace("\\n", "\n");
+        let args = (1..).map_while(|i| self.registers.get(&format!("P{}", i)).cloned()).collect::<Vec<Data>>();
+        let max = self.max_value_size();
+        let formatted = format_string(&format, &args, max.unwrap_or(usize::MAX)).map_err(|error| RuntimeError::new(ErrorKind::Argument, format!("[{}] {}", instruction, error)))?;
+        match max {
+            Some(max) if formatted.len() > max => Err(self.value_too_large(instruction)),
+            _ => Ok(formatted),
+        }
+    }
+
+    fn printf(&mut self) -> ExecResult {
+        let formatted = self.format_params("printf")?;
+        self.write_output("printf", &formatted)
+    }
+
+    fn format(&mut self) -> ExecResult {
+        let formatted = self.format_params("format")?;
+        self.registers.insert("RET0".to_string(), Data::String(formatted));
+        Ok(())
+    }
+
+    //a parameter register, which does not exist when .registers declared fewer than the builtin needs
+    fn param(&self, reg: &str, instruction: &str) -> Result<&Data, RuntimeError> {
+        self.registers.get(reg).ok_or_else(|| RuntimeError::new(ErrorKind::Argument, format!("[{}] Expected a value in {}, but the program has no register {}", instruction, reg, reg)))
+    }
+
+    fn string_param(&self, reg: &str, instruction: &str) -> Result<String, RuntimeError> {
+        match self.param(reg, instruction)? {
+            Data::String(s) => Ok(s.to_string()),
+            data => Err(RuntimeError::new(ErrorKind::Type, format!("[{}] Expected a string in {}, got: {}", instruction, reg, data.to_string()))),
+        }
+    }
+
+    fn file_param(&mut self, instruction: &str) -> Result<&mut OpenFile, RuntimeError> {
+        let handle = match self.registers.get("P0").unwrap() {
+            Data::File(handle) => *handle,
+            data => return Err(RuntimeError::new(ErrorKind::Type, format!("[{}] Expected a file handle in P0, got: {}", instruction, data.to_string()))),
+        };
+        match self.files.get_mut(&handle) {
+            Some(file) => Ok(file),
+            None => Err(RuntimeError::new(ErrorKind::Io, format!("[{}] File handle {} is closed", instruction, handle))),
+        }
+    }
+
+    pub(crate) fn io_error(instruction: &str, path: &str, error: io::Error) -> RuntimeError {
+        RuntimeError::new(ErrorKind::Io, format!("[{}] {}: {}", instruction, path, error))
+    }
+
+    //P0 = path, P1 = "r", "w" or "a", the handle is returned in RET0
+    fn file_open(&mut self) -> ExecResult {
+        let path = self.string_param("P0", "file_open")?;
+        let mode = self.string_param("P1", "file_open")?;
+        let file = match mode.as_str() {
+            "r" => {
+                let resolved = self.sandbox.check(&path, false, "file_open")?;
+                File::open(resolved).map(|file| OpenFile::Read(BufReader::new(file)))
+            }
+            "w" | "a" => {
+                let resolved = self.sandbox.check(&path, true, "file_open")?;
+                OpenOptions::new().create(true).write(true).append(mode == "a").truncate(mode == "w").open(resolved).map(OpenFile::Write)
+            }
+            _ => return Err(RuntimeError::new(ErrorKind::Argument, format!("[file_open] Unknown mode {}, expected r, w or a", mode))),
+        }.map_err(|error| RunTime::io_error("file_open", &path, error))?;
+
+        let handle = self.next_file_handle;
+        self.next_file_handle += 1;
+        self.files.insert(handle, file);
+        self.registers.insert("RET0".to_string(), Data::File(handle));
+        Ok(())
+    }
+
+    //P0 = handle, the line without its newline is returned in RET0, sets the eof flag at the end of the file
+    fn file_read_line(&mut self) -> ExecResult {
+        let max = self.max_value_size();
+        let take = max.map_or(u64::MAX, |max| max as u64 + 2); //room for the \r\n after a line of max bytes
+        let OpenFile::Read(reader) = self.file_param("file_read_line")? else {
+            return Err(RuntimeError::new(ErrorKind::Io, "[file_read_line] File was not opened for reading".to_string()));
+        };
+        let mut line = String::new();
+        let read = reader.take(take).read_line(&mut line).map_err(|error| RunTime::io_error("file_read_line", "read", error))?;
+
+        self.eof_flag = read == 0;
+        let line = line.strip_suffix('\n').unwrap_or(&line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if max.is_some_and(|max| line.len() > max) {
+            return Err(self.value_too_large("file_read_line"));
+        }
+        self.registers.insert("RET0".to_string(), if read == 0 { Data::Null } else { Data::String(line.to_string()) });
+        Ok(())
+    }
+
+    //P0 = handle, the rest of the file is returned in RET0
+    fn file_read_all(&mut self) -> ExecResult {
+        let max = self.max_value_size();
+        let take = max.map_or(u64::MAX, |max| max as u64 + 1); //one byte more tells a value of exactly max bytes from a longer one
+        let OpenFile::Read(reader) = self.file_param("file_read_all")? else {
+            return Err(RuntimeError::new(ErrorKind::Io, "[file_read_all] File was not opened for reading".to_string()));
+        };
+        let mut contents = String::new();
+        let read = reader.take(take).read_to_string(&mut contents).map_err(|error| RunTime::io_error("file_read_all", "read", error))?;
+        if max.is_some_and(|max| read > max) {
+            return Err(self.value_too_large("file_read_all"));
+        }
+
+        self.eof_flag = true;
+        self.registers.insert("RET0".to_string(), Data::String(contents));
+        Ok(())
+    }
+
+    //P0 = handle, P1 = value to write
+    fn file_write(&mut self) -> ExecResult {
+        let data = self.param("P1", "file_write")?.to_string();
+        let OpenFile::Write(file) = self.file_param("file_write")? else {
+            return Err(RuntimeError::new(ErrorKind::Io, "[file_write] File was not opened for writing".to_string()));
+        };
+        file.write_all(data.as_bytes()).map_err(|error| RunTime::io_error("file_write", "write", error))
+    }
+
+    //P0 = path, P1 = value to add to the end of the file, creating it if needed
+    fn file_append(&mut self) -> ExecResult {
+        let path = self.string_param("P0", "file_append")?;
+        let resolved = self.sandbox.check(&path, true, "file_append")?;
+        let data = self.param("P1", "file_append")?.to_string();
+        OpenOptions::new().create(true).append(true).open(resolved)
+            .and_then(|mut file| file.write_all(data.as_bytes()))
+            .map_err(|error| RunTime::io_error("file_append", &path, error))
+    }
+
+    //P0 = handle
+    fn file_close(&mut self) -> ExecResult {
+        self.file_param("file_close")?;
+        if let Data::File(handle) = self.registers.get("P0").unwrap() {
+            self.files.remove(handle);
+        }
+        Ok(())
+    }
+
+    //P0 = path, RET0 = whether it exists
+    fn file_exists(&mut self) -> ExecResult {
+        let path = self.string_param("P0", "file_exists")?;
+        let resolved = self.sandbox.check(&path, false, "file_exists")?;
+        self.registers.insert("RET0".to_string(), Data::Bool(resolved.exists()));
+        Ok(())
+    }
+
+    //P0 = path
+    fn file_delete(&mut self) -> ExecResult {
+        let path = self.string_param("P0", "file_delete")?;
+        let resolved = self.sandbox.check(&path, true, "file_delete")?;
+        fs::remove_file(resolved).map_err(|error| RunTime::io_error("file_delete", &path, error))
+    }
+
+    //P0 = directory, RET0 = list of the names in it, sorted
+    fn dir_list(&mut self) -> ExecResult {
+        let path = self.string_param("P0", "dir_list")?;
+        let resolved = self.sandbox.check(&path, false, "dir_list")?;
+        let mut names = fs::read_dir(resolved)
+            .and_then(|entries| entries.map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string())).collect::<io::Result<Vec<String>>>())
+            .map_err(|error| RunTime::io_error("dir_list", &path, error))?;
+        names.sort();
+        self.registers.insert("RET0".to_string(), Data::List(names.into_iter().map(Data::String).collect()));
+        Ok(())
+    }
+
+    //P0 = name, RET0 = its value or null if it is not set
+    fn getenv(&mut self) -> ExecResult {
+        let name = self.string_param("P0", "getenv")?;
+        let value = std::env::var(&name).map_or(Data::Null, Data::String);
+        self.registers.insert("RET0".to_string(), value);
+        Ok(())
+    }
+
+    //P0 = name, P1 = value
+    fn setenv(&mut self) -> ExecResult {
+        let name = self.string_param("P0", "setenv")?;
+        let value = self.param("P1", "setenv")?.to_string();
+        if name.is_empty() || name.contains('=') || name.contains('\0') || value.contains('\0') {
+            return Err(RuntimeError::new(ErrorKind::Argument, format!("[setenv] Invalid environment variable: {}", name)));
+        }
+        std::env::set_var(name, value);
+        Ok(())
+    }
+
+    //RET0 = seconds since the unix epoch, RET1 = milliseconds since the unix epoch
+    fn time(&mut self) -> ExecResult {
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        self.registers.insert("RET0".to_string(), Data::Int(since_epoch.as_secs() as i64));
+        self.registers.insert("RET1".to_string(), Data::Int(since_epoch.as_millis() as i64));
+        Ok(())
+    }
+
+    //P0 = format string, empty or null for the default, RET0 = the current UTC date and time
+    fn date(&mut self) -> ExecResult {
+        let format = match self.registers.get("P0").unwrap() {
+            Data::String(format) if !format.is_empty() => format.to_string(),
+            Data::String(_) | Data::Null => "%Y-%m-%d %H:%M:%S".to_string(),
+            data => return Err(RuntimeError::new(ErrorKind::Type, format!("[date] Expected a format string or null in P0, got: {}", data.to_string()))),
+        };
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        self.registers.insert("RET0".to_string(), Data::String(format_date(&format, since_epoch.as_secs() as i64)));
+        Ok(())
+    }
+
+    //RET0 = milliseconds since the program started, only useful for measuring durations
+    fn clock(&mut self) -> ExecResult {
+        let elapsed = self.started.elapsed().as_micros() as f64 / 1000_f64;
+        self.registers.insert("RET0".to_string(), Data::Float(elapsed));
+        Ok(())
+    }
+
+    //P0 = milliseconds to sleep for
+    fn sleep(&mut self) -> ExecResult {
+        let Data::Int(millis) = self.registers.get("P0").unwrap() else {
+            return Err(RuntimeError::new(ErrorKind::Type, format!("[sleep] Expected an integer number of milliseconds in P0, got: {}", self.registers.get("P0").unwrap().to_string())));
+        };
+        if *millis < 0 {
+            return Err(RuntimeError::new(ErrorKind::Argument, format!("[sleep] Attempted to sleep for a negative duration: {}", millis)));
+        }
+        let duration = Duration::from_millis(*millis as u64);
+        match self.time_left() {
+            Some(left) if left < duration => {
+                std::thread::sleep(left);
+                Err(self.out_of_time())
+            }
+            _ => {
+                std::thread::sleep(duration);
+                Ok(())
+            }
+        }
+    }
+
+    //RET0 = the current working directory
+    fn cwd(&mut self) -> ExecResult {
+        let dir = std::env::current_dir().map_err(|error| RunTime::io_error("cwd", ".", error))?;
+        self.registers.insert("RET0".to_string(), Data::String(dir.to_string_lossy().to_string()));
+        Ok(())
+    }
+
+    //P0 = program, P1 = list of arguments, P2 = text to write to its stdin or null
+    //RET0 = exit code (-1 if it was killed by a signal), RET1 = stdout, RET2 = stderr
+    fn exec(&mut self) -> ExecResult {
+        let program = self.string_param("P0", "exec")?;
+        if !self.sandbox.exec {
+            return Err(RuntimeError::new(ErrorKind::Permission, format!("[exec] Running {} is not allowed, use --allow-exec", program)));
+        }
+        let args = match self.param("P1", "exec")? {
+            Data::List(items) => items.iter().map(|item| item.to_string()).collect(),
+            Data::Null => Vec::new(),
+            data => return Err(RuntimeError::new(ErrorKind::Type, format!("[exec] Expected a list of arguments or null in P1, got: {}", data.to_string()))),
+        };
+        let input = match self.param("P2", "exec")? {
+            Data::Null => None,
+            data => Some(data.to_string()),
+        };
+
+        let mut child = process::Command::new(&program)
+            .args(&args)
+            .stdin(if input.is_some() { process::Stdio::piped() } else { process::Stdio::null() })
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn()
+            .map_err(|error| RunTime::io_error("exec", &program, error))?;
+        //written from another thread so a program that fills its stdout before reading everything cannot deadlock us
+        let writer = match (input, child.stdin.take()) {
+            (Some(input), Some(mut stdin)) => Some(std::thread::spawn(move || stdin.write_all(input.as_bytes()))),
+            _ => None,
+        };
+        //read from other threads too so the time limit can be checked while the program runs. Only a byte over the
+        //size limit is kept, the rest is still read so the program does not block on a full pipe
+        let max = self.max_value_size();
+        let take = max.map_or(u64::MAX, |max| max as u64 + 1);
+        let collect = |pipe: Option<Box<dyn Read + Send>>| pipe.map(|mut pipe| std::thread::spawn(move || {
+            let mut bytes = Vec::new();
+            (&mut pipe).take(take).read_to_end(&mut bytes)?;
+            io::copy(&mut pipe, &mut io::sink()).map(|_| bytes)
+        }));
+        let stdout = collect(child.stdout.take().map(|pipe| Box::new(pipe) as Box<dyn Read + Send>));
+        let stderr = collect(child.stderr.take().map(|pipe| Box::new(pipe) as Box<dyn Read + Send>));
+
+        let status = loop {
+            let Some(left) = self.time_left() else {
+                break child.wait();
+            };
+            match child.try_wait() {
+                Ok(Some(status)) => break Ok(status),
+                Ok(None) if !left.is_zero() => std::thread::sleep(left.min(Duration::from_millis(5))),
+                Ok(None) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(self.out_of_time());
+                }
+                Err(error) => break Err(error),
+            }
+        }.map_err(|error| RunTime::io_error("exec", &program, error))?;
+        if let Some(writer) = writer {
+            //a program exiting without reading all of its input is not an error
+            let _ = writer.join();
+        }
+        let finish = |pipe: Option<std::thread::JoinHandle<io::Result<Vec<u8>>>>| -> Result<String, RuntimeError> {
+            let bytes = match pipe.map(|reader| reader.join()) {
+                Some(Ok(read)) => read.map_err(|error| RunTime::io_error("exec", &program, error))?,
+                _ => Vec::new(),
+            };
+            match max {
+                Some(max) if bytes.len() > max => Err(self.value_too_large("exec")),
+                _ => Ok(String::from_utf8_lossy(&bytes).to_string()),
+            }
+        };
+        let (stdout, stderr) = (finish(stdout)?, finish(stderr)?);
+
+        self.registers.insert("RET0".to_string(), Data::Int(status.code().map_or(-1, |code| code as i64)));
+        self.registers.insert("RET1".to_string(), Data::String(stdout));
+        self.registers.insert("RET2".to_string(), Data::String(stderr));
+        Ok(())
+    }
+
+    fn exit(&mut self) -> ExecResult {
+        let Data::Int(code) = self.registers.get("P0").unwrap() else {
+            return Err(RuntimeError::new(ErrorKind::Type, format!("[exit] Attempted to exit with non-integer code: {}", self.registers.get("P0").unwrap().to_string())));
+        };
+        let Some(code) = exit_code(*code) else {
+            return Err(RuntimeError::new(ErrorKind::Argument, format!("[exit] Attempted to exit with a code outside 0..=255: {}", code)));
+        };
+        if self.verbose {
+            self.write_output("exit", &format!("Process exited with code '{}'\n", code))?;
+        }
+        self.signal = Some(Signal::Exit(code));
+        Ok(())
+    }
+
+    fn atexit(&mut self) -> ExecResult {
+        match self.registers.get("P0").unwrap() {
+            Data::Function(name) => {
+                self.atexit_handlers.push(name.to_string());
+                Ok(())
+            }
+            data => Err(RuntimeError::new(ErrorKind::Type, format!("[atexit] Attempted to register non-function as exit handler: {}", data.to_string()))),
+        }
+    }
+
+    //P0 = path to save a snapshot of the running program to, `xasm --resume path` continues from it.
+    //RET0 is false after saving and true in the resumed program
+    fn checkpoint(&mut self) -> ExecResult {
+        let path = self.string_param("P0", "checkpoint")?;
+        let resolved = self.sandbox.check(&path, true, "checkpoint")?;
+        self.registers.insert("RET0".to_string(), Data::Bool(true));
+        let snapshot = self.snapshot();
+        self.registers.insert("RET0".to_string(), Data::Bool(false));
+        fs::write(resolved, snapshot).map_err(|error| RunTime::io_error("checkpoint", &path, error))
+    }
+}
@@ -0,0 +1,66 @@
+#[derive(Clone)]
+pub enum Data {
+    Int(i64),
+    Float(f64),
+    String(String),
+    Bool(bool),
+    Function(String), //reference to a user defined or builtin function, created with &name
+    Struct(String, Vec<(String, Data)>), //struct name and its fields in declaration order, created with NEW
+    List(Vec<Data>),
+    File(usize), //handle of a file opened with file_open
+    Null,
+}
+
+//same as a derived Debug, except structs are shown with their field names
+impl std::fmt::Debug for Data {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Data::Int(i) => f.debug_tuple("Int").field(i).finish(),
+            Data::Float(fl) => f.debug_tuple("Float").field(fl).finish(),
+            Data::String(s) => f.debug_tuple("String").field(s).finish(),
+            Data::Bool(b) => f.debug_tuple("Bool").field(b).finish(),
+            Data::Function(name) => f.debug_tuple("Function").field(name).finish(),
+            Data::Struct(name, fields) => {
+                let mut debug = f.debug_struct(name);
+                for (field, value) in fields {
+                    debug.field(field, value);
+                }
+                debug.finish()
+            }
+            Data::List(items) => f.debug_tuple("List").field(items).finish(),
+            Data::File(handle) => f.debug_tuple("File").field(handle).finish(),
+            Data::Null => write!(f, "Null"),
+        }
+    }
+}
+
+#[allow(clippy::inherent_to_string)]
+impl Data {
+    pub fn to_string(&self) -> String {
+        match self {
+            Data::Int(i) => i.to_string(),
+            Data::Float(f) => f.to_string(),
+            Data::String(s) => s.to_string().replace("\\n", "\n"),
+            Data::Bool(b) => b.to_string(),
+            Data::Function(name) => format!("&{}", name),
+            Data::Struct(name, fields) => {
+                let fields = fields.iter().map(|(field, value)| format!("{}: {}", field, value.to_nested_string())).collect::<Vec<String>>();
+                format!("{} {{ {} }}", name, fields.join(", "))
+            }
+            Data::List(items) => {
+                let items = items.iter().map(|item| item.to_nested_string()).collect::<Vec<String>>();
+                format!("[{}]", items.join(", "))
+            }
+            Data::File(handle) => format!("<file {}>", handle),
+            Data::Null => "null".to_string(),
+        }
+    }
+
+    //strings inside structs and lists are quoted so they can be told apart
+    pub(crate) fn to_nested_string(&self) -> String {
+        match self {
+            Data::String(s) => format!("\"{}\"", s),
+            _ => self.to_string(),
+        }
+    }
+}
@@ -0,0 +1,533 @@
+use std::cmp::Ordering;
+use std::io::Write;
+
+use crate::data::Data;
+use crate::error::RuntimeError;
+use crate::parse::{resolve_aliases, split_whitespace_not_in_quotes};
+use crate::runtime::RunTime;
+use crate::step::{Location, Status};
+
+const HELP: &str = "\
+break function|line   pause before the function is called or the source line is executed (b)
+watch target          pause after the target changes, a register, stack for the stack depth or a flag name
+log target            print every change of the target and keep running
+delete n              remove breakpoint or watchpoint n
+breakpoints           list the breakpoints and watchpoints
+step                  execute the next instruction, stopping inside the functions it calls (s)
+next                  execute the next instruction, running the functions it calls to their end (n)
+out                   run until the current function returns (finish)
+continue              run until a breakpoint, a watchpoint, a BREAKPOINT instruction or the end of the program (c)
+print register        show the value of a register (p)
+set register value    change a register, the value is written like an operand of MOV
+registers             show every register that is not null (regs)
+stack                 show the stack, bottom first
+backtrace             show the active calls, innermost first (bt)
+where                 show the next instruction
+help                  show this list
+quit                  stop debugging (q)
+An empty line repeats the last command.
+break, watch and log take a condition after if, comparisons of registers, flags, stack, hits and values
+with == != < <= > >= joined by and. hits counts the times the point was reached: break square if n > 2 and hits == 3";
+
+const FLAGS: [&str; 6] = ["equal", "greater", "lesser", "zero", "eof", "error"];
+const OPERATORS: [&str; 6] = ["==", "!=", "<", "<=", ">", ">="];
+
+//where a breakpoint pauses the program
+#[derive(Debug, Clone, PartialEq)]
+pub enum Breakpoint {
+    Function(String), //before the first instruction of every call to the function
+    Line(usize), //before the instruction on this line of the source
+}
+
+impl std::fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Breakpoint::Function(name) => write!(f, "function {}", name),
+            Breakpoint::Line(line) => write!(f, "line {}", line),
+        }
+    }
+}
+
+//what a watchpoint looks at, it is checked after every instruction
+#[derive(Debug, Clone, PartialEq)]
+pub enum Watch {
+    Register(String), //a register, alias or parameter, undefined while the name means nothing
+    StackDepth,
+    Flag(String), //equal, greater, lesser, zero, eof or error
+}
+
+impl Watch {
+    fn parse(target: &str) -> Watch {
+        match target {
+            "stack" => Watch::StackDepth,
+            flag if FLAGS.contains(&flag) => Watch::Flag(flag.to_string()),
+            name => Watch::Register(name.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Watch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Watch::Register(name) => write!(f, "{}", name),
+            Watch::StackDepth => write!(f, "stack depth"),
+            Watch::Flag(name) => write!(f, "{} flag", name),
+        }
+    }
+}
+
+//how far continuing runs before pausing again, breakpoints pause it earlier
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Until {
+    Step, //the next instruction anywhere
+    Next, //the next instruction in this function or a caller
+    Out, //the next instruction in a caller
+    Break, //only breakpoints
+}
+
+enum Kind {
+    Break(Breakpoint),
+    Watch(Watch, Option<Data>), //with the value it had last time it was checked
+}
+
+//a breakpoint or watchpoint, the condition has to hold as well for it to pause or log
+struct Point {
+    kind: Kind,
+    condition: Option<Condition>,
+    log: bool, //print where it was hit and keep running
+    hits: u64, //times the breakpoint was reached or the watched value changed, conditions see it as hits
+}
+
+impl Point {
+    fn name(&self) -> &str {
+        match self.kind {
+            Kind::Break(_) => "Breakpoint",
+            Kind::Watch(..) => "Watchpoint",
+        }
+    }
+}
+
+//left operator right comparisons that all have to hold, kept with the text they were written as
+struct Condition {
+    text: String,
+    comparisons: Vec<[String; 3]>,
+}
+
+impl Condition {
+    fn parse(text: &str) -> Result<Condition, String> {
+        let tokens = split_whitespace_not_in_quotes(text).into_iter().filter(|token| !token.is_empty()).collect::<Vec<String>>();
+        let comparisons = tokens.split(|token| token == "and").map(|comparison| match comparison {
+            [left, operator, right] if OPERATORS.contains(&operator.as_str()) => Ok([left.clone(), operator.clone(), right.clone()]),
+            _ => Err(format!("Invalid condition: {}, expected comparisons like R3 > 100 joined by and", text)),
+        }).collect::<Result<Vec<[String; 3]>, String>>()?;
+        Ok(Condition { text: tokens.join(" "), comparisons })
+    }
+}
+
+//runs a program one command at a time, what it prints goes to the program output as usual
+pub struct Debugger {
+    runtime: RunTime,
+    output: Box<dyn Write>, //where the debugger itself prints
+    points: Vec<Option<Point>>, //deleted ones stay as None so the numbers do not change
+    logging: bool, //nobody is typing commands, so points never pause
+    last_location: Option<Location>, //the instruction executed last, which is what changed a watched value
+    last_command: String,
+}
+
+impl Debugger {
+    pub fn new<W: Write + 'static>(runtime: RunTime, output: W) -> Debugger {
+        Debugger { runtime, output: Box::new(output), points: Vec::new(), logging: false, last_location: None, last_command: String::new() }
+    }
+
+    pub fn runtime(&mut self) -> &mut RunTime {
+        &mut self.runtime
+    }
+
+    //every breakpoint and watchpoint prints where it was hit and keeps running, and the end of the
+    //program is not announced, for running with points from the command line
+    pub fn set_logging(&mut self, logging: bool) {
+        self.logging = logging;
+    }
+
+    //returns the number of the breakpoint, starting at 1
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        self.add(Kind::Break(breakpoint), None, false)
+    }
+
+    //returns the number of the watchpoint, breakpoints and watchpoints are numbered together
+    pub fn add_watchpoint(&mut self, watch: Watch) -> usize {
+        let value = self.watched_value(&watch);
+        self.add(Kind::Watch(watch, value), None, false)
+    }
+
+    //adds a point written like the break, watch and log commands, such as watch R3 if R3 > 100
+    pub fn add_point(&mut self, command: &str) -> Result<usize, String> {
+        let (command, rest) = command.trim().split_once(char::is_whitespace).ok_or(format!("Expected a target: {} target [if condition]", command.trim()))?;
+        let (target, condition) = match rest.trim().split_once(char::is_whitespace) {
+            Some((target, condition)) => match condition.trim().strip_prefix("if") {
+                Some(condition) if condition.is_empty() || condition.starts_with(char::is_whitespace) => (target, Some(Condition::parse(condition)?)),
+                _ => return Err(format!("Expected if and a condition after the target: {} target [if condition]", command)),
+            },
+            None => (rest.trim(), None),
+        };
+        match command {
+            "break" | "b" => {
+                let breakpoint = match target.parse::<usize>() {
+                    Ok(line) => Breakpoint::Line(line),
+                    Err(_) => Breakpoint::Function(target.to_string()),
+                };
+                Ok(self.add(Kind::Break(breakpoint), condition, false))
+            }
+            "watch" | "log" => {
+                let watch = Watch::parse(target);
+                let value = self.watched_value(&watch);
+                Ok(self.add(Kind::Watch(watch, value), condition, command == "log"))
+            }
+            command => Err(format!("Unknown kind of point: {}, expected break, watch or log", command)),
+        }
+    }
+
+    fn add(&mut self, kind: Kind, condition: Option<Condition>, log: bool) -> usize {
+        self.points.push(Some(Point { kind, condition, log, hits: 0 }));
+        self.points.len()
+    }
+
+    //starts the program paused before its first instruction
+    pub fn start(&mut self, args: &[String]) -> Result<(), RuntimeError> {
+        self.runtime.start(args)?;
+        //starting sets up registers like ARGV, which is not a change made by the program
+        for point in self.points.iter_mut().flatten() {
+            if let Kind::Watch(watch, value) = &mut point.kind {
+                *value = watched_value(&self.runtime, watch);
+            }
+        }
+        match self.runtime.location() {
+            Some(location) => {
+                let breakpoint = self.check_breakpoints(&location);
+                if !self.logging {
+                    self.say(&format!("{} at {}", breakpoint.as_deref().unwrap_or("Paused"), describe(&location)));
+                }
+            }
+            None => self.resume(Until::Step),
+        }
+        Ok(())
+    }
+
+    //runs one debugger command, returns false once debugging should stop
+    pub fn command(&mut self, line: &str) -> bool {
+        let line = match line.trim() {
+            "" => self.last_command.clone(),
+            line => line.to_string(),
+        };
+        self.last_command = line.clone();
+        let (command, rest) = line.split_once(char::is_whitespace).map_or((line.as_str(), ""), |(command, rest)| (command, rest.trim()));
+
+        match (command, rest) {
+            ("" | "help" | "h", _) => self.say(HELP),
+            ("quit" | "q", _) => return false,
+            ("break" | "b", "") => self.say("Expected a function name or line number: break function|line [if condition]"),
+            ("watch" | "log", "") => self.say(&format!("Expected a register, stack or a flag: {} target [if condition]", command)),
+            ("break" | "b" | "watch" | "log", _) => match self.add_point(&line) {
+                Ok(number) => {
+                    let description = self.describe_point(number);
+                    self.say(&description);
+                }
+                Err(message) => self.say(&message),
+            },
+            ("delete", number) => match number.parse::<usize>().ok().and_then(|n| self.points.get_mut(n.wrapping_sub(1))) {
+                Some(point @ Some(_)) => {
+                    let name = point.take().unwrap().name().to_lowercase();
+                    self.say(&format!("Deleted {} {}", name, number));
+                }
+                _ => self.say(&format!("No breakpoint or watchpoint {}", number)),
+            },
+            ("breakpoints", _) => {
+                let list = (1..=self.points.len()).filter(|&n| self.points[n - 1].is_some()).map(|n| self.describe_point(n)).collect::<Vec<String>>();
+                self.say(&if list.is_empty() { "No breakpoints or watchpoints".to_string() } else { list.join("\n") });
+            }
+            ("step" | "s", _) => self.resume(Until::Step),
+            ("next" | "n", _) => self.resume(Until::Next),
+            ("out" | "finish", _) => self.resume(Until::Out),
+            ("continue" | "c", _) => self.resume(Until::Break),
+            ("print" | "p", "") => self.say("Expected a register: print register"),
+            ("print" | "p", name) => match self.runtime.register(name) {
+                Some(value) => self.say(&format!("{} = {}", name, value.to_nested_string())),
+                None => self.say(&format!("No register named {}", name)),
+            },
+            ("set", assignment) => match assignment.split_once(char::is_whitespace) {
+                Some((name, value)) => {
+                    let name = name.trim_end_matches(',');
+                    let line = resolve_aliases(&format!("MOV {}, {}", name, value.trim()), &self.runtime.aliases);
+                    match self.runtime.execute_line(&line) {
+                        Ok(()) => self.say(&format!("{} = {}", name, self.runtime.register(name).map_or(String::new(), |value| value.to_nested_string()))),
+                        Err(error) => self.say(&error.message),
+                    }
+                }
+                None => self.say("Expected a register and a value: set register value"),
+            },
+            ("registers" | "regs", _) => self.show_registers(),
+            ("stack", _) => {
+                let stack = self.runtime.stack().iter().enumerate().map(|(i, value)| format!("{}: {}", i, value.to_nested_string())).collect::<Vec<String>>();
+                self.say(&if stack.is_empty() { "The stack is empty".to_string() } else { stack.join("\n") });
+            }
+            ("backtrace" | "bt", _) => {
+                let calls = self.runtime.backtrace().iter().enumerate().map(|(i, location)| format!("#{} {}", i, describe(location))).collect::<Vec<String>>();
+                self.say(&if calls.is_empty() { "No active calls".to_string() } else { calls.join("\n") });
+            }
+            ("where", _) => match self.runtime.location() {
+                Some(location) => self.say(&format!("Paused at {}", describe(&location))),
+                None => self.say("The program is not running"),
+            },
+            (command, _) => self.say(&format!("Unknown command: {}, type help for a list", command)),
+        }
+        true
+    }
+
+    fn say(&mut self, text: &str) {
+        //the debugger has nowhere else to report a broken output to
+        let _ = writeln!(self.output, "{}", text).and_then(|_| self.output.flush());
+    }
+
+    fn show_registers(&mut self) {
+        let mut registers = self.runtime.registers.iter().filter(|(_, value)| !matches!(value, Data::Null)).collect::<Vec<_>>();
+        registers.sort_by_key(|(name, _)| register_order(name));
+        let lines = registers.iter().map(|(name, value)| format!("{} = {}", name, value.to_nested_string())).collect::<Vec<String>>();
+        self.say(&if lines.is_empty() { "Every register is null".to_string() } else { lines.join("\n") });
+    }
+
+    //one line about point n, the same as when it was added with the hits so far
+    fn describe_point(&self, number: usize) -> String {
+        let Some(point) = &self.points[number - 1] else {
+            return String::new();
+        };
+        let mut description = match &point.kind {
+            Kind::Break(breakpoint) => format!("Breakpoint {} at {}", number, breakpoint),
+            Kind::Watch(watch, value) => format!("Watchpoint {} on {}, now {}", number, watch, show(value)),
+        };
+        if let Some(condition) = &point.condition {
+            description += &format!(" if {}", condition.text);
+        }
+        if point.log {
+            description += ", logging changes";
+        }
+        if point.hits > 0 {
+            description += &format!(" (hits: {})", point.hits);
+        }
+        description
+    }
+
+    fn watched_value(&self, watch: &Watch) -> Option<Data> {
+        watched_value(&self.runtime, watch)
+    }
+
+    //hits and flag names, then registers, then anything MOV accepts as a value
+    fn operand(&self, token: &str, hits: u64) -> Result<Data, String> {
+        if token == "hits" {
+            return Ok(Data::Int(hits as i64));
+        }
+        match Watch::parse(token) {
+            Watch::Register(name) => match self.runtime.register(&name) {
+                Some(value) => Ok(value.clone()),
+                None => self.runtime.value_of(&name).map_err(|error| error.message),
+            },
+            watch => Ok(self.watched_value(&watch).unwrap_or(Data::Null)),
+        }
+    }
+
+    fn holds(&self, condition: &Condition, hits: u64) -> Result<bool, String> {
+        for [left, operator, right] in &condition.comparisons {
+            let (left, right) = (self.operand(left, hits)?, self.operand(right, hits)?);
+            let ordering = match (&left, &right) {
+                (Data::Int(a), Data::Int(b)) => a.partial_cmp(b),
+                (Data::Int(a), Data::Float(b)) => (*a as f64).partial_cmp(b),
+                (Data::Float(a), Data::Int(b)) => a.partial_cmp(&(*b as f64)),
+                (Data::Float(a), Data::Float(b)) => a.partial_cmp(b),
+                (Data::String(a), Data::String(b)) => a.partial_cmp(b),
+                (Data::Bool(a), Data::Bool(b)) => a.partial_cmp(b),
+                _ => None,
+            };
+            //values that cannot be ordered, like lists, can still be equal
+            let equal = ordering.map_or_else(|| format!("{:?}", left) == format!("{:?}", right), |ordering| ordering == Ordering::Equal);
+            let holds = match operator.as_str() {
+                "==" => equal,
+                "!=" => !equal,
+                "<" => ordering == Some(Ordering::Less),
+                "<=" => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                ">" => ordering == Some(Ordering::Greater),
+                _ => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            };
+            if !holds {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    //counts a hit of point n and checks its condition, a condition that cannot be evaluated counts as met
+    //so the reason can be looked at
+    fn hit(&mut self, number: usize) -> bool {
+        let Some(point) = self.points[number - 1].as_mut() else {
+            return false;
+        };
+        point.hits += 1;
+        let point = self.points[number - 1].as_ref().unwrap();
+        let Some(condition) = &point.condition else {
+            return true;
+        };
+        match self.holds(condition, point.hits) {
+            Ok(holds) => holds,
+            Err(message) => {
+                let message = format!("{} {}: cannot evaluate {}: {}", point.name(), number, condition.text, message);
+                self.say(&message);
+                true
+            }
+        }
+    }
+
+    //the breakpoint that pauses before location, logging points only print that they were hit
+    fn check_breakpoints(&mut self, location: &Location) -> Option<String> {
+        let mut pause = None;
+        if location.instruction.split_whitespace().next().is_some_and(|instruction| instruction.eq_ignore_ascii_case("BREAKPOINT")) {
+            if self.logging {
+                self.say(&format!("BREAKPOINT at {}", describe(location)));
+            } else {
+                pause = Some("BREAKPOINT".to_string());
+            }
+        }
+        for number in 1..=self.points.len() {
+            let Some(Point { kind: Kind::Break(breakpoint), log, .. }) = &self.points[number - 1] else {
+                continue;
+            };
+            let log = *log || self.logging;
+            let reached = match breakpoint {
+                Breakpoint::Function(name) => *name == location.function && location.index == 0,
+                Breakpoint::Line(line) => *line == location.line,
+            };
+            if !reached || !self.hit(number) {
+                continue;
+            }
+            if log {
+                self.say(&format!("Breakpoint {} at {}", number, describe(location)));
+            } else if pause.is_none() {
+                pause = Some(format!("Breakpoint {}", number));
+            }
+        }
+        pause
+    }
+
+    //reports the watched values that changed since the last check, returns whether one should pause
+    fn check_watchpoints(&mut self) -> bool {
+        let mut pause = false;
+        for number in 1..=self.points.len() {
+            let Some(Point { kind: Kind::Watch(watch, last), log, .. }) = &self.points[number - 1] else {
+                continue;
+            };
+            let value = self.watched_value(watch);
+            if format!("{:?}", value) == format!("{:?}", last) {
+                continue;
+            }
+            let mut message = format!("Watchpoint {}: {} changed from {} to {}", number, watch, show(last), show(&value));
+            if let Some(location) = &self.last_location {
+                message += &format!(" by {}", describe(location));
+            }
+            let log = *log || self.logging;
+            if let Some(Point { kind: Kind::Watch(_, last), .. }) = &mut self.points[number - 1] {
+                *last = value;
+            }
+            if self.hit(number) {
+                self.say(&message);
+                pause |= !log;
+            }
+        }
+        pause
+    }
+
+    fn resume(&mut self, until: Until) {
+        if !matches!(self.runtime.status(), Status::Running | Status::WaitingForInput) {
+            self.say("The program is not running");
+            return;
+        }
+
+        let depth = self.runtime.call_depth();
+        let watching = self.points.iter().flatten().any(|point| matches!(point.kind, Kind::Watch(..)));
+        let mut watched = false; //a watchpoint changed and pauses at the next instruction
+        loop {
+            if watching {
+                if let Some(location) = self.runtime.location() {
+                    self.last_location = Some(location);
+                }
+            }
+            let status = self.runtime.step();
+            if watching {
+                watched |= self.check_watchpoints();
+            }
+            match status {
+                Status::Running => {}
+                Status::WaitingForInput => {
+                    self.say("Waiting for input");
+                    return;
+                }
+                Status::Halted(code) => {
+                    if !self.logging {
+                        self.say(&format!("Program exited with code {}", code));
+                    }
+                    return;
+                }
+                Status::Errored(error) => {
+                    self.runtime.report_error(&error);
+                    if !self.logging {
+                        self.say("Program ended by an uncaught error");
+                    }
+                    return;
+                }
+            }
+
+            let Some(location) = self.runtime.location() else {
+                continue;
+            };
+            let breakpoint = self.check_breakpoints(&location);
+            let stop = watched || match until {
+                Until::Step => true,
+                Until::Next => self.runtime.call_depth() <= depth,
+                Until::Out => self.runtime.call_depth() < depth,
+                Until::Break => false,
+            };
+            if breakpoint.is_some() || stop {
+                self.say(&format!("{} at {}", breakpoint.as_deref().unwrap_or("Paused"), describe(&location)));
+                return;
+            }
+        }
+    }
+}
+
+fn watched_value(runtime: &RunTime, watch: &Watch) -> Option<Data> {
+    match watch {
+        Watch::Register(name) => runtime.register(name).cloned(),
+        Watch::StackDepth => Some(Data::Int(runtime.stack().len() as i64)),
+        Watch::Flag(name) => {
+            let flags = runtime.flags();
+            Some(Data::Bool(match name.as_str() {
+                "equal" => flags.equal,
+                "greater" => flags.greater,
+                "lesser" => flags.lesser,
+                "zero" => flags.zero,
+                "eof" => flags.eof,
+                _ => flags.error,
+            }))
+        }
+    }
+}
+
+//R2 before R10, by the letters and then the number
+pub(crate) fn register_order(name: &str) -> (String, u64) {
+    let digits = name.trim_start_matches(|c: char| !c.is_ascii_digit());
+    (name[..name.len() - digits.len()].to_string(), digits.parse::<u64>().unwrap_or(0))
+}
+
+fn show(value: &Option<Data>) -> String {
+    value.as_ref().map_or("undefined".to_string(), |value| value.to_nested_string())
+}
+
+fn describe(location: &Location) -> String {
+    format!("{} (line {}): {}", location.function, location.line, location.instruction)
+}
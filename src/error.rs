@@ -0,0 +1,113 @@
+use std::io::Write;
+
+use crate::data::Data;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    Type,
+    Register,
+    Function,
+    Argument,
+    DivideByZero,
+    Overflow, //an integer result that does not fit in 64 bits
+    Stack,
+    StackOverflow,
+    Instruction,
+    Field,
+    Io,
+    Permission, //file access outside of the directories allowed by the sandbox
+    LimitExceeded, //one of the limits set with RunTime::set_limits, cannot be caught
+    Thrown, //raised by THROW
+}
+
+impl ErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Type => "TypeError",
+            ErrorKind::Register => "RegisterError",
+            ErrorKind::Function => "FunctionError",
+            ErrorKind::Argument => "ArgumentError",
+            ErrorKind::DivideByZero => "DivideByZero",
+            ErrorKind::Overflow => "OverflowError",
+            ErrorKind::Stack => "StackError",
+            ErrorKind::StackOverflow => "StackOverflow",
+            ErrorKind::Instruction => "InstructionError",
+            ErrorKind::Field => "FieldError",
+            ErrorKind::Io => "IOError",
+            ErrorKind::Permission => "PermissionError",
+            ErrorKind::LimitExceeded => "LimitExceeded",
+            ErrorKind::Thrown => "Exception",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<ErrorKind> {
+        let kinds = [
+            ErrorKind::Type, ErrorKind::Register, ErrorKind::Function, ErrorKind::Argument, ErrorKind::DivideByZero, ErrorKind::Overflow,
+            ErrorKind::Stack, ErrorKind::StackOverflow, ErrorKind::Instruction, ErrorKind::Field, ErrorKind::Io,
+            ErrorKind::Permission, ErrorKind::LimitExceeded, ErrorKind::Thrown,
+        ];
+        kinds.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+//a runtime fault or THROWn value, unwinds until a TRY handler catches it
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub value: Data, //the value passed to THROW, null for runtime faults
+    pub backtrace: Vec<String>, //function and instruction of every call the error unwound through, innermost first
+}
+
+impl RuntimeError {
+    pub fn new(kind: ErrorKind, message: String) -> RuntimeError {
+        RuntimeError { kind, message, value: Data::Null, backtrace: Vec::new() }
+    }
+
+    pub fn report(&self, errors: &mut dyn Write) {
+        let mut report = if self.kind == ErrorKind::Thrown {
+            format!("[throw] Uncaught exception: {}\n", self.message)
+        } else {
+            format!("{}\n", self.message)
+        };
+        if !self.backtrace.is_empty() {
+            report.push_str("Backtrace:\n");
+            for call in &self.backtrace {
+                report.push_str(&format!("    at {}\n", call));
+            }
+        }
+        //nowhere left to report a failure to write the report
+        let _ = errors.write_all(report.as_bytes());
+        let _ = errors.flush();
+    }
+}
+
+pub type ExecResult = Result<(), RuntimeError>;
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind.name(), self.message)
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+//a program that could not be loaded, bad declarations, directives or constants
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadError {
+    pub message: String,
+}
+
+impl LoadError {
+    pub(crate) fn new(message: String) -> LoadError {
+        LoadError { message }
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for LoadError {}
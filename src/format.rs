@@ -0,0 +1,170 @@
+use crate::data::Data;
+
+//largest width or precision a spec can ask for, larger ones would allocate without bound
+const MAX_WIDTH: usize = 65536;
+
+//renders one {:spec} placeholder, spec is [[fill]align][+][0][width][.precision][type] like Rust's format!.
+//width and precision stop at max_len + 1, anything longer than max_len is too long anyway
+fn format_value(value: &Data, spec: &str, max_len: usize) -> Result<String, String> {
+    let mut chars = spec.chars().peekable();
+    let mut fill = ' ';
+    let mut align = None;
+    let first_two = spec.chars().take(2).collect::<Vec<char>>();
+    if first_two.len() == 2 && matches!(first_two[1], '<' | '>' | '^') {
+        fill = first_two[0];
+        align = Some(first_two[1]);
+        chars.nth(1);
+    } else if matches!(first_two.first(), Some('<' | '>' | '^')) {
+        align = first_two.first().copied();
+        chars.next();
+    }
+
+    let sign = chars.peek() == Some(&'+'); //+ in front of numbers that are not negative
+    if sign {
+        chars.next();
+    }
+    let zero_pad = align.is_none() && chars.peek() == Some(&'0');
+    if zero_pad {
+        chars.next();
+    }
+
+    let mut width = String::new();
+    while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+        width.push(*c);
+        chars.next();
+    }
+    let mut precision = None;
+    if chars.peek() == Some(&'.') {
+        chars.next();
+        let mut digits = String::new();
+        while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+            digits.push(*c);
+            chars.next();
+        }
+        let digits = digits.parse::<usize>().ok().filter(|precision| *precision <= MAX_WIDTH);
+        precision = Some(digits.ok_or_else(|| format!("Invalid precision in {{:{}}}, it can be at most {}", spec, MAX_WIDTH))?.min(max_len.saturating_add(1)));
+    }
+    let kind = chars.collect::<String>();
+    let width = if width.is_empty() { Some(0) } else { width.parse::<usize>().ok().filter(|width| *width <= MAX_WIDTH) };
+    let width = width.ok_or_else(|| format!("Invalid width in {{:{}}}, it can be at most {}", spec, MAX_WIDTH))?.min(max_len.saturating_add(1));
+
+    let text = match (kind.as_str(), value) {
+        ("x", Data::Int(i)) => format!("{:x}", i),
+        ("X", Data::Int(i)) => format!("{:X}", i),
+        ("b", Data::Int(i)) => format!("{:b}", i),
+        ("o", Data::Int(i)) => format!("{:o}", i),
+        ("x" | "X" | "b" | "o", _) => return Err(format!("{{:{}}} expects an integer, got: {}", spec, value.to_string())),
+        ("", Data::Float(f)) if precision.is_some() => format!("{:.*}", precision.unwrap(), f),
+        ("", Data::Int(i)) if precision.is_some() => format!("{:.*}", precision.unwrap(), *i as f64),
+        ("", Data::String(s)) if precision.is_some() => s.chars().take(precision.unwrap()).collect(),
+        ("", _) => value.to_string(),
+        _ => return Err(format!("Unknown format type in {{:{}}}", spec)),
+    };
+    let numeric = matches!(value, Data::Int(_) | Data::Float(_));
+    let text = if sign && numeric && !text.starts_with('-') { format!("+{}", text) } else { text };
+
+    let len = text.chars().count();
+    if len >= width {
+        return Ok(text);
+    }
+    let padding = width - len;
+    if zero_pad && numeric {
+        let (sign, digits) = if text.starts_with(['-', '+']) { text.split_at(1) } else { ("", text.as_str()) };
+        return Ok(format!("{}{}{}", sign, "0".repeat(padding), digits));
+    }
+    let fill = |n: usize| fill.to_string().repeat(n);
+    Ok(match align.unwrap_or(if numeric { '>' } else { '<' }) {
+        '<' => format!("{}{}", text, fill(padding)),
+        '^' => format!("{}{}{}", fill(padding / 2), text, fill(padding - padding / 2)),
+        _ => format!("{}{}", fill(padding), text),
+    })
+}
+
+//replaces every {} / {:spec} placeholder with the next argument, {{ and }} are literal braces.
+//stops early once the result is longer than max_len, so a size limit is never exceeded by much
+pub(crate) fn format_string(format: &str, args: &[Data], max_len: usize) -> Result<String, String> {
+    let mut output = String::new();
+    let mut args = args.iter();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err(format!("Unclosed placeholder in format string: {}", format)),
+                    }
+                }
+                let spec = match placeholder.strip_prefix(':') {
+                    Some(spec) => spec,
+                    None if placeholder.is_empty() => "",
+                    None => return Err(format!("Invalid placeholder {{{}}}, expected {{}} or {{:spec}}", placeholder)),
+                };
+                let Some(value) = args.next() else {
+                    return Err(format!("Not enough arguments for format string: {}", format));
+                };
+                output.push_str(&format_value(value, spec, max_len)?);
+                if output.len() > max_len {
+                    break;
+                }
+            }
+            '}' => return Err(format!("Unmatched }} in format string: {}", format)),
+            c => output.push(c),
+        }
+    }
+
+    Ok(output)
+}
+
+//formats a unix timestamp in UTC, supports %Y %m %d %H %M %S %s and %%
+pub(crate) fn format_date(format: &str, timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86400);
+    let seconds = timestamp.rem_euclid(86400);
+
+    //days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    let mut output = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => output.push_str(&format!("{:04}", year)),
+            Some('m') => output.push_str(&format!("{:02}", month)),
+            Some('d') => output.push_str(&format!("{:02}", day)),
+            Some('H') => output.push_str(&format!("{:02}", seconds / 3600)),
+            Some('M') => output.push_str(&format!("{:02}", seconds / 60 % 60)),
+            Some('S') => output.push_str(&format!("{:02}", seconds % 60)),
+            Some('s') => output.push_str(&timestamp.to_string()),
+            Some('%') => output.push('%'),
+            Some(other) => {
+                output.push('%');
+                output.push(other);
+            }
+            None => output.push('%'),
+        }
+    }
+    output
+}
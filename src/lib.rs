@@ -0,0 +1,26 @@
+//the Xasm interpreter, load a program into a RunTime and run it, or drive it one line at a time
+mod builtins;
+mod data;
+mod debugger;
+mod error;
+mod format;
+mod json;
+mod limits;
+mod parse;
+mod profile;
+mod runtime;
+mod sandbox;
+mod snapshot;
+mod step;
+mod streams;
+mod trace;
+
+pub use data::Data;
+pub use debugger::{Breakpoint, Debugger, Watch};
+pub use error::{ErrorKind, ExecResult, LoadError, RuntimeError};
+pub use limits::Limits;
+pub use profile::Profiler;
+pub use runtime::{Flags, RunTime};
+pub use step::{Location, Status};
+pub use streams::{CapturedRun, SharedBuffer};
+pub use trace::Tracer;
@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+use crate::data::Data;
+use crate::error::{ErrorKind, ExecResult, RuntimeError};
+use crate::runtime::RunTime;
+
+//upper bounds on what a program may use, everything is unlimited by default.
+//going over one raises a LimitExceeded error, which TRY cannot catch
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Limits {
+    pub instructions: Option<u64>, //instructions executed by a single run or execute, every loop iteration and function call counts as one too
+    pub time: Option<Duration>, //wall-clock time of a single run or execute
+    pub stack_depth: Option<usize>, //values on the stack, checked before the .stack size of the program so it wins when it is not larger
+    pub call_depth: Option<usize>, //nested function calls
+    pub value_size: Option<usize>, //bytes in a string, items in a list or fields in a struct, nested values included
+}
+
+//bytes of a string, or items / fields of a collection plus the size of everything inside it
+fn value_size(value: &Data) -> usize {
+    match value {
+        Data::String(s) => s.len(),
+        Data::List(items) => items.len() + items.iter().map(value_size).sum::<usize>(),
+        Data::Struct(_, fields) => fields.len() + fields.iter().map(|(_, value)| value_size(value)).sum::<usize>(),
+        _ => 0,
+    }
+}
+
+fn exceeded(message: String) -> RuntimeError {
+    RuntimeError::new(ErrorKind::LimitExceeded, format!("[limit] {}", message))
+}
+
+impl RunTime {
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    //the instruction and time limits are counted from here
+    pub(crate) fn start_budget(&mut self) {
+        self.instructions_executed = 0;
+        self.deadline = self.limits.time.map(|time| Instant::now() + time);
+    }
+
+    //called for every instruction, loop iteration and function call
+    pub(crate) fn count_instruction(&mut self) -> ExecResult {
+        self.instructions_executed += 1;
+        if let Some(limit) = self.limits.instructions {
+            if self.instructions_executed > limit {
+                return Err(exceeded(format!("Exceeded the limit of {} instructions", limit)));
+            }
+        }
+        if self.deadline.is_some_and(|deadline| Instant::now() > deadline) {
+            return Err(self.out_of_time());
+        }
+        Ok(())
+    }
+
+    //how long a builtin that blocks may take before the time limit runs out, None without a time limit
+    pub(crate) fn time_left(&self) -> Option<Duration> {
+        self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    pub(crate) fn out_of_time(&self) -> RuntimeError {
+        exceeded(format!("Exceeded the time limit of {}ms", self.limits.time.unwrap_or_default().as_millis()))
+    }
+
+    //the most bytes a builtin may read or build into one value, None without a size limit
+    pub(crate) fn max_value_size(&self) -> Option<usize> {
+        self.limits.value_size
+    }
+
+    pub(crate) fn value_too_large(&self, instruction: &str) -> RuntimeError {
+        exceeded(format!("[{}] The result exceeded the size limit of {}", instruction, self.limits.value_size.unwrap_or_default()))
+    }
+
+    pub(crate) fn check_stack_depth(&self) -> ExecResult {
+        match self.limits.stack_depth {
+            Some(limit) if self.stack.len() >= limit => Err(exceeded(format!("Exceeded the limit of {} values on the stack", limit))),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_call_depth(&self) -> ExecResult {
+        match self.limits.call_depth {
+            Some(limit) if self.frames.len() >= limit => Err(exceeded(format!("Exceeded the limit of {} nested function calls", limit))),
+            _ => Ok(()),
+        }
+    }
+
+    //checks the value in a register, or the register holding the struct for a field path like R0.x
+    pub(crate) fn check_value_size(&self, reg: &str) -> ExecResult {
+        let Some(limit) = self.limits.value_size else {
+            return Ok(());
+        };
+        let reg = reg.split('.').next().unwrap_or_default();
+        match self.registers.get(reg) {
+            Some(value) if value_size(value) > limit => Err(exceeded(format!("{} exceeded the size limit of {}", reg, limit))),
+            _ => Ok(()),
+        }
+    }
+
+    //a native function can write to any register, so all of them are checked after one ran
+    pub(crate) fn check_register_sizes(&self) -> ExecResult {
+        let Some(limit) = self.limits.value_size else {
+            return Ok(());
+        };
+        match self.registers.iter().find(|(_, value)| value_size(value) > limit) {
+            Some((reg, _)) => Err(exceeded(format!("{} exceeded the size limit of {}", reg, limit))),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_pushed_size(&self, value: &Data) -> ExecResult {
+        match self.limits.value_size {
+            Some(limit) if value_size(value) > limit => Err(exceeded(format!("Pushed value exceeded the size limit of {}", limit))),
+            _ => Ok(()),
+        }
+    }
+}
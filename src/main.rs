@@ -1,1125 +1,374 @@
-use std::fs::read_to_string;
+use std::fs;
+use std::io::{self, BufWriter, Write};
 use std::path::Path;
-use std::{collections::HashMap, process};
-use std::io::{self, Write};
-use std::time::Instant;
+use std::process;
+use std::time::{Duration, Instant};
+
+use xasm::{Debugger, Limits, Profiler, RunTime, Status, Tracer};
 
 #[allow(non_upper_case_globals)]
 const flush: fn() = || io::stdout().flush().unwrap();
 
-#[derive(Debug, Clone)]
-enum Data {
-    Int(i64),
-    Float(f64),
-    String(String),
-    Bool(bool),
-    Null,
-}
+const USAGE: &str = "Usage: xasm [debug] [--entry name] [--registers=n] [--verbose] [--allow-read=dir] [--allow-write=dir] [--allow-exec] [--max-instructions=n] [--timeout=ms] [--max-stack=n] [--max-call-depth=n] [--max-value-size=n] [--break=spec] [--watch=spec] [--trace[=file]] [--trace-function=name] [--profile[=file]] [--profile-folded=file] (file.xasm [args...] | --resume snapshot.json)";
 
-#[allow(clippy::inherent_to_string)]
-impl Data {
-    fn to_string(&self) -> String {
-        match self {
-            Data::Int(i) => i.to_string(),
-            Data::Float(f) => f.to_string(),
-            Data::String(s) => s.to_string().replace("\\n", "\n"),
-            Data::Bool(b) => b.to_string(),
-            Data::Null => "null".to_string(),
+//value of a --name=n option, exits if it is not a number
+fn limit_value(option: &str) -> u64 {
+    let (name, value) = option.split_once('=').unwrap();
+    match value.parse::<u64>() {
+        Ok(value) => value,
+        Err(_) => {
+            eprintln!("Invalid value for {}: {}", name, value);
+            process::exit(1);
         }
     }
 }
 
-type FunctionBody = Vec<String>;
-
-fn split_whitespace_not_in_quotes(s: &str) -> Vec<String> {
-    let mut split = Vec::new();
-    let mut current = String::new();
-    let mut in_quotes = false;
-
-    for c in s.chars() {
-        if c == '\"' {
-            in_quotes = !in_quotes;
-        }
-        if c == ' ' && !in_quotes {
-            split.push(current);
-            current = String::new();
-        } else {
-            current.push(c);
+//a buffered file for --trace, exits if it cannot be created
+fn create(file: &str) -> BufWriter<fs::File> {
+    match fs::File::create(file) {
+        Ok(created) => BufWriter::new(created),
+        Err(error) => {
+            eprintln!("Cannot write {}: {}", file, error);
+            process::exit(1);
         }
     }
-
-    split.push(current);
-
-    split
 }
 
-#[derive(Debug)]
-struct RunTime {
-    stack: Vec<Data>,
-    registers: HashMap<String, Data>,
-    functions: HashMap<String, FunctionBody>,
-    equal_flag: bool,
-    greater_flag: bool,
-    lesser_flag: bool,
-    zero_flag: bool,
-}
-
-impl RunTime {
-    fn new(code: String) -> RunTime {
-        let mut functions = HashMap::new();
-        let mut registers: HashMap<String, Data> = HashMap::new();
-
-        //initialize registers
-        for i in 0..=12 {
-            registers.insert(format!("R{}", i), Data::Null); //general purpose registers rX
-            registers.insert(format!("P{}", i), Data::Null); //parameter registers pX
-            registers.insert(format!("RET{}", i), Data::Null); //return registers (for functions) retX
-        }
-        registers.insert(format!("L{}", 0), Data::Null); //loop register, determines how many the loop instruction should run
-
-        //find all the functions
-        let mut lines = code.lines().filter(|l| !l.is_empty());
-
-        while let Some(line) = lines.next() {
-            if line.starts_with("fun ") {
-                let split = line.split_whitespace().collect::<Vec<&str>>();
-
-                if split.len() != 2 {
-                    eprintln!("Invalid function declaration: {}", line);
-                    process::exit(1);
-                }
-
-                let name = split[1].to_string();
-                let mut body: Vec<String> = Vec::new();
-                for l in lines.by_ref() {
-                    if l.starts_with("end") {
-                        break;
-                    }
-                    if l.trim_start().starts_with("//") || l.is_empty() { //ignore comments and empty lines
-                        continue;
-                    }
-                    body.push(l.trim_start().to_string());
-                }
-                functions.insert(name, body);
-            }
-        }
-
-        RunTime {
-            stack: Vec::new(),
-            registers,
-            functions,
-            equal_flag: false,
-            greater_flag: false,
-            lesser_flag: false,
-            zero_flag: false,
-        }
-    }
-
-    fn is_register(&self, reg: &String) -> bool {
-        self.registers.contains_key(reg)
-    }
-
-    fn determine_type(&self, data: &String) -> Data {
-        if data.starts_with('\"') && data.ends_with('\"') {
-            Data::String(data[1..data.len() - 1].to_string())
-        } else if data == "true" || data == "false" {
-            Data::Bool(data == "true")
-        } else if data.contains('.') {
-            Data::Float(data.parse::<f64>().unwrap())
-        } else if data.parse::<i64>().is_ok() {
-            Data::Int(data.parse::<i64>().unwrap())
-        }
-        else {
-            eprintln!("Unknown data type: {}", data);
+//runs the program under the debugger, reading its commands from stdin. Without anyone typing commands
+//the points only log to stderr while the program runs to its end
+fn debug(runtime: RunTime, args: &[String], resumed: bool, points: &[String], interactive: bool) -> ! {
+    let mut debugger = if interactive { Debugger::new(runtime, io::stdout()) } else { Debugger::new(runtime, io::stderr()) };
+    debugger.set_logging(!interactive);
+    for point in points {
+        if let Err(message) = debugger.add_point(point) {
+            eprintln!("{}", message);
             process::exit(1);
         }
     }
-
-    fn execute_line(&mut self, line: &str) {
-        let split = split_whitespace_not_in_quotes(line);
-        if split.len() < 2 {
-            eprintln!("Invalid instruction: {}", line);
-            return;
-        }
-
-        let first_arg = split[1].to_string().replace(',', "");
-
-
-        match split[0].trim().to_uppercase().as_str() {
-            "MOV" => self.mov(first_arg, split[2].to_string()),
-            "PUSH" => self.push(first_arg),
-            "POP" => self.pop(first_arg),
-            "INC" => self.inc(&first_arg),
-            "DEC" => self.dec(&first_arg),
-            "SUB" => self.sub(&first_arg, &split[2].to_string()),
-            "ADD" => self.add(&first_arg, &split[2].to_string()),
-            "DIV" => self.div(&first_arg, &split[2].to_string()),
-            "MUL" => self.mul(&first_arg, &split[2].to_string()),
-            "CMP" => self.cmp(&first_arg, &split[2].to_string()),
-            "JMP" => self.jmp(&first_arg),
-            "JE" => self.je(&first_arg),
-            "JNE" => self.jne(&first_arg),
-            "JG" => self.jg(&first_arg),
-            "JGE" => self.jge(&first_arg),
-            "JL" => self.jl(&first_arg),
-            "JLE" => self.jle(&first_arg),
-            "JZ" => self.jz(&first_arg),
-            "JNZ" => self.jnz(&first_arg),
-            "SETG" => self.setg(&first_arg),
-            "SETGE" => self.setge(&first_arg),
-            "SETL" => self.setl(&first_arg),
-            "SETLE" => self.setle(&first_arg),
-            "XOR" => self.xor(&first_arg, &split[2].to_string()),
-            "LOOP" => self.loop_(&first_arg),
-            "LOOPNODEC" => self.loop_no_dec(&first_arg),
-
-            _ => println!("Unknown command: {}", split[0]),
-        }
-    }
-
-    fn push(&mut self, data: String) {
-        if self.is_register(&data) {
-            self.stack.push(self.registers.get(&data).unwrap().clone());
-        } else {
-            self.stack.push(self.determine_type(&data));
-        }
-    }
-
-    fn pop(&mut self, out_reg: String) {
-        if !self.is_register(&out_reg) {
-            eprintln!("[pop] Attempted to pop into non-existant register: {}", out_reg);
-            process::exit(1);
-        }
-
-        if let Some(data) = self.stack.pop() {
-            self.registers.insert(out_reg, data);
-        }
-        else {
-            eprintln!("[pop] Attempted to pop from empty stack");
+    if !resumed {
+        if let Err(error) = debugger.start(args) {
+            debugger.runtime().report_error(&error);
             process::exit(1);
         }
     }
 
-    fn mov(&mut self, reg: String, data: String) {
-        if !self.is_register(&reg) {
-            eprintln!("[mov] Attempted to move data into a non-existant register: {}", reg);
-            process::exit(1);
-        }
-        
-        if self.is_register(&data) { //move register to register
-            self.registers.insert(reg, self.registers.get(&data).unwrap().clone());
-        }
-        else { //move hardcoded data to register
-            self.registers.insert(reg, self.determine_type(&data));
+    if !interactive {
+        debugger.command("continue");
+    } else {
+        loop {
+            let mut command = String::new();
+            print!("debug> ");
+            flush();
+            if !matches!(io::stdin().read_line(&mut command), Ok(read) if read > 0) {
+                println!();
+                break;
+            }
+            if !debugger.command(&command) {
+                break;
+            }
         }
     }
-
-    fn inc(&mut self, reg: &String) {
-        if !self.is_register(reg) {
-            eprintln!("[inc] Attempted to increment non-existant register: {}", reg);
-            process::exit(1);
-        }
-
-        let data = self.registers.get(reg).unwrap();
-        match data {
-            Data::Int(i) => {
-                self.zero_flag = i + 1 == 0;
-                self.registers.insert(reg.to_string(), Data::Int(i + 1));
-
-            },
-            Data::Float(f) => {
-                self.zero_flag = f + 1.0 == 0.0;
-                self.registers.insert(reg.to_string(), Data::Float(f + 1.0));
-            },
-            _ => {
-                eprintln!("[inc] Attempted to increment non-numeric register: {}", reg);
-                process::exit(1);
-            }
-        };
+    match debugger.runtime().status() {
+        Status::Halted(code) => process::exit(code),
+        Status::Errored(_) => process::exit(1),
+        _ => process::exit(0), //quit before the program ended
     }
+}
 
-    fn dec(&mut self, reg: &String) {
-        if !self.is_register(reg) {
-            eprintln!("[dec] Attempted to decrement non-existant register: {}", reg);
-            process::exit(1);
-        }
-
-        let data = self.registers.get(reg).unwrap();
-        match data {
-            Data::Int(i) => {
-                self.zero_flag = i - 1 == 0;
-                self.registers.insert(reg.to_string(), Data::Int(i - 1));
-            },
-            Data::Float(f) => {
-                self.zero_flag = f - 1.0 == 0.0;
-                self.registers.insert(reg.to_string(), Data::Float(f - 1.0));
-            },
-            _ => {
-                eprintln!("[dec] Attempted to decrement non-numeric register: {}", reg);
-                process::exit(1);
+fn run_from_args(args: &[String], debugging: bool) {
+    let mut entry = None;
+    let mut register_count = None;
+    let mut resume = None; //snapshot saved by checkpoint to continue instead of running a file
+    let mut verbose = false;
+    let mut allow_exec = false;
+    let mut allowed = Vec::new(); //(write, dir) in the order they were given
+    let mut limits = Limits::default();
+    let mut points = Vec::new(); //break and watch commands for the debugger
+    let mut trace = None; //Some(None) traces to stderr, Some(Some(file)) to a file
+    let mut traced_functions = Vec::new();
+    let mut profile = None; //Some(None) prints the report to stderr, Some(Some(file)) writes it to a file
+    let mut folded = None; //file for the folded call stacks of the profile
+    let mut i = 0;
+    while i < args.len() && args[i].starts_with("--") {
+        match args[i].as_str() {
+            "--entry" if i + 1 < args.len() => {
+                entry = Some(args[i + 1].to_string());
+                i += 2;
             }
-        };
-    }
-
-    fn add(&mut self, reg: &String, data: &String) {
-        if !self.is_register(reg) {
-            eprintln!("[add] Attempted to add to non-existant register: {}", reg);
-            process::exit(1);
-        }
-
-        if self.is_register(data) {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.registers.get(data).unwrap();
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = i + j == 0;
-                            self.registers.insert(reg.to_string(), Data::Int(i + j));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = *i as f64 + j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 + j));
-                        },
-                        _ => {
-                            eprintln!("[add] Attempted to add non-numeric data to register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                Data::Float(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = i + *j as f64 == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i + *j as f64));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = i + j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i + j));
-                        },
-                        _ => {
-                            eprintln!("[add] Attempted to add non-numeric data to register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                Data::String(i) => {
-                    match data_data {
-                        Data::String(j) => self.registers.insert(reg.to_string(), Data::String(i.to_string() + j)),
-                        _ => {
-                            eprintln!("[add] Attempted to add non-string data to register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                _ => {
-                    eprintln!("[add] Attempted to add to non-numeric / non-string register: {}", reg);
-                    process::exit(1);
-                }
+            "--resume" if i + 1 < args.len() => {
+                resume = Some(args[i + 1].to_string());
+                i += 2;
             }
-        }
-        else {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.determine_type(data);
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = j + i == 0;
-                            self.registers.insert(reg.to_string(), Data::Int(i + j));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = *i as f64 + j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 + j));
-                        },
-                        _ => {
-                            eprintln!("[add] Attempted to add non-numeric data to register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                Data::Float(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = i + j as f64 == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i + j as f64));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = i + j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i + j));
-                        },
-                        _ => {
-                            eprintln!("[add] Attempted to add non-numeric data to register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                _ => {
-                    eprintln!("[add] Attempted to add to non-numeric register: {}", reg);
-                    process::exit(1);
-                }
+            option if option.starts_with("--registers=") => {
+                register_count = Some(limit_value(option) as usize);
+                i += 1;
             }
-        }
-    }
-
-    fn sub(&mut self, reg: &String, data: &String) {
-        if !self.is_register(reg) {
-            eprintln!("[sub] Attempted to subtract from non-existant register: {}", reg);
-            process::exit(1);
-        }
-
-        if self.is_register(data) {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.registers.get(data).unwrap();
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = i - j == 0;
-                            self.registers.insert(reg.to_string(), Data::Int(i - j));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = *i as f64 - j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 - j));
-                        },
-                        _ => {
-                            eprintln!("[sub] Attempted to subtract non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                Data::Float(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = i - *j as f64 == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i - *j as f64));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = i - j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i - j));
-                        },
-                        _ => {
-                            eprintln!("[sub] Attempted to subtract non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                _ => {
-                    eprintln!("[sub] Attempted to subtract from non-numeric register: {}", reg);
-                    process::exit(1);
-                }
+            "--verbose" => {
+                verbose = true;
+                i += 1;
             }
-        }
-        else {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.determine_type(data);
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = i - j == 0;
-                            self.registers.insert(reg.to_string(), Data::Int(i - j));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = *i as f64 - j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 - j));
-                        },
-                        _ => {
-                            eprintln!("[sub] Attempted to subtract non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                Data::Float(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = i - j as f64 == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i - j as f64));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = i - j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i - j));
-                        },
-                        _ => {
-                            eprintln!("[sub] Attempted to subtract non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                _ => {
-                    eprintln!("[sub] Attempted to subtract from non-numeric register: {}", reg);
-                    process::exit(1);
-                }
+            "--allow-exec" => {
+                allow_exec = true;
+                i += 1;
             }
-        }
-    }
-
-    fn div(&mut self, reg: &String, data: &String) {
-        if !self.is_register(reg) {
-            eprintln!("[div] Attempted to divide non-existant register: {}", reg);
-            process::exit(1);
-        }
-
-        if self.is_register(data) {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.registers.get(data).unwrap();
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            if *j == 0 {
-                                eprintln!("[div] Attempted to divide by zero.");
-                                process::exit(1);
-                            }
-                            else {
-                                self.zero_flag = *i / *j == 0;
-                                self.registers.insert(reg.to_string(), Data::Float(*i as f64 / *j as f64));
-                            }
-                        }
-                        Data::Float(j) => {
-                            if *j == 0.0 {
-                                eprintln!("[div] Attempted to divide by zero.");
-                                process::exit(1);
-                            }
-                            else {
-                                self.zero_flag = *i as f64 / j == 0.0;
-                                self.registers.insert(reg.to_string(), Data::Float(*i as f64 / j));
-                            }
-                        }
-
-                        _ => {
-                            eprintln!("[div] Attempted to divide non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    }
-                }
-
-                Data::Float(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            if *j == 0 {
-                                eprintln!("[div] Attempted to divide by zero.");
-                                process::exit(1);
-                            }
-                            else {
-                                self.zero_flag = i / *j as f64 == 0.0;
-                                self.registers.insert(reg.to_string(), Data::Float(i / *j as f64));
-                            }
-                        }
-                        Data::Float(j) => {
-                            if *j == 0.0 {
-                                eprintln!("[div] Attempted to divide by zero.");
-                                process::exit(1);
-                            }
-                            else {
-                                self.zero_flag = i / j == 0.0;
-                                self.registers.insert(reg.to_string(), Data::Float(i / j));
-                            }
-                        }
-                        _ => {
-                            eprintln!("[div] Attempted to divide non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    }
-                }
-
-                _ => {
-                    eprintln!("[div] Attempted to divide from non-numeric register: {}", reg);
-                    process::exit(1);
-                }
+            option if option.starts_with("--allow-read=") || option.starts_with("--allow-write=") => {
+                let (kind, dir) = option.split_once('=').unwrap();
+                allowed.push((kind == "--allow-write", dir.to_string()));
+                i += 1;
             }
-        }
-
-        else {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.determine_type(data);
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            if j == 0 {
-                                eprintln!("[div] Attempted to divide by zero.");
-                                process::exit(1);
-                            }
-                            else {
-                                self.zero_flag = *i / j == 0;
-                                self.registers.insert(reg.to_string(), Data::Float(*i as f64 / j as f64));
-                            }
-                        }
-                        Data::Float(j) => {
-                            if j == 0.0 {
-                                eprintln!("[div] Attempted to divide by zero.");
-                                process::exit(1);
-                            }
-                            else {
-                                self.zero_flag = *i as f64 / j == 0.0;
-                                self.registers.insert(reg.to_string(), Data::Float(*i as f64 / j));
-                            }
-                        }
-
-                        _ => {
-                            eprintln!("[div] Attempted to divide non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    }
-                }
-
-                Data::Float(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            if j == 0 {
-                                eprintln!("[div] Attempted to divide by zero.");
-                                process::exit(1);
-                            }
-                            else {
-                                self.zero_flag = i / j as f64 == 0.0;
-                                self.registers.insert(reg.to_string(), Data::Float(i / j as f64));
-                            }
-                        }
-                        Data::Float(j) => {
-                            if j == 0.0 {
-                                eprintln!("[div] Attempted to divide by zero.");
-                                process::exit(1);
-                            }
-                            else {
-                                self.zero_flag = i / j == 0.0;
-                                self.registers.insert(reg.to_string(), Data::Float(i / j));
-                            }
-                        }
-                        _ => {
-                            eprintln!("[div] Attempted to divide non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    }
-                }
-
-                _ => {
-                    eprintln!("[div] Attempted to divide from non-numeric register: {}", reg);
-                    process::exit(1);
-                }
+            option if option.starts_with("--max-instructions=") => {
+                limits.instructions = Some(limit_value(option));
+                i += 1;
             }
-        }
-    }
-
-    fn mul(&mut self, reg: &String, data: &String) {
-        if !self.is_register(reg) {
-            eprintln!("[mul] Attempted to multiply non-existant register: {}", reg);
-            process::exit(1);
-        }
-
-        if self.is_register(data) {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.registers.get(data).unwrap();
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = *i * j == 0;
-                            self.registers.insert(reg.to_string(), Data::Int(i * j));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = *i as f64 * j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 * j));
-                        },
-                        _ => {
-                            eprintln!("[mul] Attempted to multiply non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                Data::Float(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = i * *j as f64 == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i * *j as f64));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = i * j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i * j));
-                        },
-                        _ => {
-                            eprintln!("[mul] Attempted to multiply non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                _ => {
-                    eprintln!("[mul] Attempted to multiply from non-numeric register: {}", reg);
-                    process::exit(1);
-                }
+            option if option.starts_with("--timeout=") => {
+                limits.time = Some(Duration::from_millis(limit_value(option)));
+                i += 1;
             }
-        }
-
-        else {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.determine_type(data);
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = *i * j == 0;
-                            self.registers.insert(reg.to_string(), Data::Int(i * j));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = *i as f64 * j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 * j));
-                        },
-                        _ => {
-                            eprintln!("[mul] Attempted to multiply non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                Data::Float(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = i * j as f64 == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i * j as f64));
-                        },
-                        Data::Float(j) => {
-                            self.zero_flag = i * j == 0.0;
-                            self.registers.insert(reg.to_string(), Data::Float(i * j));
-                        },
-                        _ => {
-                            eprintln!("[mul] Attempted to multiply non-numeric data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                _ => {
-                    eprintln!("[mul] Attempted to multiply from non-numeric register: {}", reg);
-                    process::exit(1);
-                }
+            option if option.starts_with("--max-stack=") => {
+                limits.stack_depth = Some(limit_value(option) as usize);
+                i += 1;
             }
-        }
-    }
-
-    fn xor(&mut self, reg: &String, data: &String) {
-        if !self.is_register(reg) {
-            eprintln!("[xor] Attempted to xor non-existant register: {}", reg);
-            process::exit(1);
-        }
-
-        if self.is_register(data) {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.registers.get(data).unwrap();
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = *i ^ j == 0;
-                            self.registers.insert(reg.to_string(), Data::Int(i ^ j));
-                        },
-                        _ => {
-                            eprintln!("[xor] Attempted to xor non-integer data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                _ => {
-                    eprintln!("[xor] Attempted to xor from non-numeric register: {}", reg);
-                    process::exit(1);
-                }
+            option if option.starts_with("--max-call-depth=") => {
+                limits.call_depth = Some(limit_value(option) as usize);
+                i += 1;
             }
-        }
-
-        else {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.determine_type(data);
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.zero_flag = *i ^ j == 0;
-                            self.registers.insert(reg.to_string(), Data::Int(i ^ j));
-                        },
-                        _ => {
-                            eprintln!("[xor] Attempted to xor non-integer data from register: {}", reg);
-                            process::exit(1);
-                        }
-                    };
-                }
-
-                _ => {
-                    eprintln!("[xor] Attempted to xor from non-numeric register: {}", reg);
-                    process::exit(1);
-                }
+            option if option.starts_with("--max-value-size=") => {
+                limits.value_size = Some(limit_value(option) as usize);
+                i += 1;
             }
-        }
-    }
-
-    fn cmp(&mut self, reg: &String, data: &String) {
-        if !self.is_register(reg) {
-            eprintln!("[cmp] Attempted to compare non-existant register: {}", reg);
-            process::exit(1);
-        }
-
-        if self.is_register(data) {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.registers.get(data).unwrap();
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.equal_flag = i == j;
-                            self.greater_flag = i > j;
-                            self.lesser_flag = i < j;
-                        }
-
-                        Data::Float(j) => {
-                            self.equal_flag = *i as f64 == *j;
-                            self.greater_flag = *i as f64 > *j;
-                            self.lesser_flag = (*i as f64) < *j;
-                        }
-
-                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
-                    }
-                }
-
-                Data::Bool(i) => {
-                    match data_data {
-                        Data::Bool(j) => {
-                            self.equal_flag = i == j;
-                            self.greater_flag = false;
-                            self.lesser_flag = false;
-                        }
-
-                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
-                    }
-                }
-
-                Data::Float(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.equal_flag = *i == *j as f64;
-                            self.greater_flag = *i > *j as f64;
-                            self.lesser_flag = *i < *j as f64;
-                        }
-
-                        Data::Float(j) => {
-                            self.equal_flag = i == j;
-                            self.greater_flag = i > j;
-                            self.lesser_flag = i < j;
-                        }
-
-                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
-                    }
-                }
-
-                Data::String(i) => {
-                    match data_data {
-                        Data::String(j) => {
-                            self.equal_flag = i == j;
-                            self.greater_flag = false;
-                            self.lesser_flag = false;
-                        }
-
-                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
-                    }
-                }
-
-                _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+            option if option.starts_with("--break=") || option.starts_with("--watch=") => {
+                let (kind, spec) = option.split_once('=').unwrap();
+                points.push(format!("{} {}", &kind[2..], spec));
+                i += 1;
             }
-        }
-        else {
-            let reg_data = self.registers.get(reg).unwrap();
-            let data_data = self.determine_type(data);
-            match reg_data {
-                Data::Int(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.equal_flag = *i == j;
-                            self.greater_flag = *i > j;
-                            self.lesser_flag = *i < j;
-                        }
-
-                        Data::Float(j) => {
-                            self.equal_flag = *i as f64 == j;
-                            self.greater_flag = *i as f64 > j;
-                            self.lesser_flag = (*i as f64) < j;
-                        }
-
-                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
-                    }
-                }
-
-                Data::Bool(i) => {
-                    match data_data {
-                        Data::Bool(j) => {
-                            self.equal_flag = *i == j;
-                            self.greater_flag = false;
-                            self.lesser_flag = false;
-                        }
-
-                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
-                    }
-                }
-
-                Data::Float(i) => {
-                    match data_data {
-                        Data::Int(j) => {
-                            self.equal_flag = *i == j as f64;
-                            self.greater_flag = *i > j as f64;
-                            self.lesser_flag = *i < j as f64;
-                        }
-
-                        Data::Float(j) => {
-                            self.equal_flag = *i == j;
-                            self.greater_flag = *i > j;
-                            self.lesser_flag = *i < j;
-                        }
-
-                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
-                    }
-                }
-
-                Data::String(i) => {
-                    match data_data {
-                        Data::String(j) => {
-                            self.equal_flag = *i == j;
-                            self.greater_flag = false;
-                            self.lesser_flag = false;
-                        }
-
-                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
-                    }
-                }
-
-                _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+            "--trace" => {
+                trace = Some(None);
+                i += 1;
+            }
+            option if option.starts_with("--trace=") => {
+                trace = Some(Some(option["--trace=".len()..].to_string()));
+                i += 1;
+            }
+            option if option.starts_with("--trace-function=") => {
+                traced_functions.push(option["--trace-function=".len()..].to_string());
+                trace.get_or_insert(None);
+                i += 1;
+            }
+            "--profile" => {
+                profile = Some(None);
+                i += 1;
+            }
+            option if option.starts_with("--profile=") => {
+                profile = Some(Some(option["--profile=".len()..].to_string()));
+                i += 1;
+            }
+            option if option.starts_with("--profile-folded=") => {
+                folded = Some(option["--profile-folded=".len()..].to_string());
+                profile.get_or_insert(None);
+                i += 1;
+            }
+            option => {
+                eprintln!("Unknown or incomplete option: {}", option);
+                eprintln!("{}", USAGE);
+                process::exit(1);
             }
         }
     }
 
-    fn jmp(&mut self, label: &String) {
-        if label == "debug" {self.debug(); return;}
-        else if label == "print" {self.print(); return;}
-        else if label == "printline" {self.printline(); return;}
-        else if label == "input" {self.input(); return;}
-        else if label == "exit" {self.exit(); return;}
-
-        if !self.functions.contains_key(label) {
-            eprintln!("[jmp] Attempted to jump to non-existant function: {}", label);
-            process::exit(1);
-        }
-
-        let function = self.functions.get(label).unwrap().clone();
-        for line in function {
-            self.execute_line(&line);
-        }
-    }
-
-    fn je(&mut self, label: &String) {
-        if self.equal_flag {
-            self.jmp(label);
-        }
-    }
-
-    fn jne(&mut self, label: &String) {
-        if !self.equal_flag {
-            self.jmp(label);
-        }
-    }
-
-    fn jg(&mut self, label: &String) {
-        if self.greater_flag {
-            self.jmp(label);
-        }
-    }
-
-    fn jge(&mut self, label: &String) {
-        if self.greater_flag || self.equal_flag {
-            self.jmp(label);
-        }
-    }
-
-    fn jl(&mut self, label: &String) {
-        if self.greater_flag || self.equal_flag {
-            self.jmp(label);
-        }
-    }
-
-    fn jle(&mut self, label: &String) {
-        if self.greater_flag || self.equal_flag {
-            self.jmp(label);
+    let loaded = match &resume {
+        Some(snapshot) => RunTime::from_snapshot(snapshot),
+        None => {
+            let Some(file) = args.get(i) else {
+                eprintln!("{}", USAGE);
+                process::exit(1);
+            };
+            let file = Path::new(file);
+            if !file.exists() {
+                eprintln!("File does not exist: {}", file.display());
+                process::exit(1);
+            }
+            RunTime::from_file(file)
         }
-    }
-
-    fn jz(&mut self, label: &String) {
-        if self.zero_flag {
-            self.jmp(label);
+    };
+    let mut runtime = match loaded {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
         }
+    };
+    if let Some(entry) = entry {
+        runtime.set_entry(&entry);
     }
-
-    fn jnz(&mut self, label: &String) {
-        if !self.zero_flag {
-            self.jmp(label);
+    if let Some(count) = register_count {
+        if let Err(error) = runtime.set_register_count(count) {
+            eprintln!("{}", error);
+            process::exit(1);
         }
     }
-
-    fn setg(&mut self, reg: &String) {
-        if self.greater_flag {
-            self.registers.insert(reg.to_string(), Data::Bool(true));
-        }
-        else {
-            self.registers.insert(reg.to_string(), Data::Bool(false));
+    runtime.set_verbose(verbose);
+    runtime.allow_exec(allow_exec);
+    runtime.set_limits(limits);
+    for (write, dir) in allowed {
+        let result = if write { runtime.allow_write(&dir) } else { runtime.allow_read(&dir) };
+        if let Err(error) = result {
+            eprintln!("{}", error);
+            process::exit(1);
         }
     }
-
-    fn setge(&mut self, reg: &String) {
-        if self.greater_flag || self.equal_flag {
-            self.registers.insert(reg.to_string(), Data::Bool(true));
-        }
-        else {
-            self.registers.insert(reg.to_string(), Data::Bool(false));
-        }
+    if [debugging || !points.is_empty(), trace.is_some(), profile.is_some()].iter().filter(|mode| **mode).count() > 1 {
+        eprintln!("Only one of debug (or --break and --watch), --trace and --profile can be used at a time");
+        process::exit(1);
     }
-
-    fn setl(&mut self, reg: &String) {
-        if self.lesser_flag {
-            self.registers.insert(reg.to_string(), Data::Bool(true));
-        }
-        else {
-            self.registers.insert(reg.to_string(), Data::Bool(false));
+    if let Some(report) = profile {
+        let mut profiler = Profiler::new();
+        let started = match resume {
+            Some(_) => Ok(()),
+            None => runtime.start(&args[i + 1..]),
+        };
+        let result = started.and_then(|_| profiler.run(&mut runtime));
+        if let Err(error) = &result {
+            runtime.report_error(error);
         }
-    }
-
-    fn setle(&mut self, reg: &String) {
-        if self.lesser_flag || self.equal_flag {
-            self.registers.insert(reg.to_string(), Data::Bool(true));
+        let mut written = match report {
+            Some(file) => fs::write(file, profiler.report()),
+            None => io::stderr().write_all(profiler.report().as_bytes()),
+        };
+        if let Some(file) = &folded {
+            written = written.and_then(|_| fs::write(file, profiler.folded()));
         }
-        else {
-            self.registers.insert(reg.to_string(), Data::Bool(false));
+        if let Err(error) = written {
+            eprintln!("Cannot write the profile: {}", error);
         }
+        process::exit(result.unwrap_or(1));
     }
-
-    fn loop_(&mut self, label: &String) {
-        match self.registers.get("L0").unwrap().clone() {
-            Data::Int(_) => {
-                loop {
-                    let value = self.registers.get("L0").unwrap().clone();
-                    if let Data::Int(i) = value {
-                        if i <= 0 {
-                            break;
-                        }
-                        self.jmp(label);
-                        self.dec(&"L0".to_string());
-                    }
-                    else {
-                        eprintln!("[loop] Attempted to loop with non-integer value");
-                        process::exit(1);
-                    }
-                }
-            }
-
-            _ => {
-                eprintln!("[loop] Attempted to loop with non-integer value");
-                process::exit(1);
-            }
+    if let Some(file) = trace {
+        let mut tracer = match file {
+            Some(file) => Tracer::new(create(&file)),
+            None => Tracer::new(io::stderr()),
+        };
+        for function in &traced_functions {
+            tracer.only(function);
         }
-    }
-
-    fn loop_no_dec(&mut self, label: &String) {
-        match self.registers.get("L0") {
-            Some(Data::Int(i)) => {
-                for _ in 0..*i {
-                    self.jmp(label);
-                }
-            }
-
-            _ => {
-                eprintln!("[loop] Attempted to loop with non-integer value");
+        let started = match resume {
+            Some(_) => Ok(()),
+            None => runtime.start(&args[i + 1..]),
+        };
+        match started.and_then(|_| tracer.run(&mut runtime)) {
+            Ok(code) => process::exit(code),
+            Err(error) => {
+                runtime.report_error(&error);
                 process::exit(1);
             }
         }
     }
-
-    fn debug(&self) {
-        println!("{:#?}", self);
-    }
-
-    fn print(&self) {
-        print!("{}", self.registers.get("P0").unwrap().to_string());
-        flush();
-    }
-
-    fn printline(&self) {
-        println!("{}", self.registers.get("P0").unwrap().to_string());
-    }
-
-    fn input(&mut self) {
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        self.registers.insert("RET0".to_string(), Data::String(input.trim().to_string()));
-    }
-
-    fn exit(&self) {
-        println!("Process exited with code '{}'", self.registers.get("P0").unwrap().to_string());
-        process::exit(0);
+    if debugging || !points.is_empty() {
+        debug(runtime, args.get(i + 1..).unwrap_or_default(), resume.is_some(), &points, debugging);
     }
-
-    fn run(&mut self) {
-        if !self.functions.contains_key("main") {
-            eprintln!("[FATAL] No main function found.");
+    let result = match resume {
+        Some(_) => runtime.resume(),
+        None => runtime.run(&args[i + 1..]),
+    };
+    match result {
+        Ok(code) => process::exit(code),
+        Err(error) => {
+            runtime.report_error(&error);
             process::exit(1);
         }
-        self.jmp(&"main".to_string());
     }
 }
 
 fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    match args.first().map(|arg| arg.as_str()) {
+        Some("debug") => run_from_args(&args[1..], true),
+        Some(_) => run_from_args(&args, false),
+        None => {}
+    }
+
     loop {
         let mut input = String::new();
         print!("1. Run a file\n2. REPL mode\n3. Exit\n> ");
         flush();
-        io::stdin().read_line(&mut input).unwrap();
+        //stop at the end of piped input instead of printing the menu forever
+        if !matches!(io::stdin().read_line(&mut input), Ok(read) if read > 0) {
+            println!();
+            break;
+        }
         match input.trim() {
             "1" => {
                 input.clear();
                 print!("Enter file name: ");
                 flush();
-                io::stdin().read_line(&mut input).unwrap();
+                if !matches!(io::stdin().read_line(&mut input), Ok(read) if read > 0) {
+                    println!();
+                    break;
+                }
                 let file = Path::new(input.trim());
                 if !file.exists() {
                     eprintln!("\nFile does not exist: {}\n", file.display());
                     continue;
                 }
-                let mut runtime = RunTime::new(read_to_string(file).unwrap());
+                let mut runtime = match RunTime::from_file(file) {
+                    Ok(runtime) => runtime,
+                    Err(error) => {
+                        eprintln!("\n{}\n", error);
+                        continue;
+                    }
+                };
                 let now = Instant::now();
-                runtime.run();
-                println!("Finished execution in {}ms", now.elapsed().as_micros() as f32 / 1000_f32);
+                match runtime.run(&[]) {
+                    Ok(code) => println!("Finished execution with code {} in {}ms", code, now.elapsed().as_micros() as f32 / 1000_f32),
+                    Err(error) => {
+                        runtime.report_error(&error);
+                        process::exit(1);
+                    }
+                }
             }
             "2" => {
                 let mut repl_input = String::new();
-                let mut runtime = RunTime::new(String::new());
+                let mut runtime = RunTime::default();
 
                 loop {
                     repl_input.clear();
                     print!("REPL> ");
                     flush();
-                    io::stdin().read_line(&mut repl_input).unwrap();
+                    if !matches!(io::stdin().read_line(&mut repl_input), Ok(read) if read > 0) {
+                        println!();
+                        break;
+                    }
                     match repl_input.trim() {
                         "exit" => {break;}
                         "clear" => {
                             print!("\x1B[2J\x1B[1;1H");
                             flush();
                         }
-                        "reset" => {runtime = RunTime::new(String::new());}
-                        _ => {
-                            runtime.execute_line(repl_input.trim());
+                        "reset" => {runtime = RunTime::default();}
+                        line if line.starts_with(":save ") => {
+                            let path = line[":save ".len()..].trim();
+                            match fs::write(path, runtime.snapshot()) {
+                                Ok(()) => println!("Saved to {}", path),
+                                Err(error) => eprintln!("Cannot write {}: {}", path, error),
+                            }
+                        }
+                        line if line.starts_with(":load ") => {
+                            match RunTime::from_snapshot(line[":load ".len()..].trim()) {
+                                Ok(loaded) => runtime = loaded,
+                                Err(error) => eprintln!("{}", error),
+                            }
+                        }
+                        line => {
+                            if let Err(error) = runtime.execute(line) {
+                                runtime.report_error(&error);
+                            }
+                            //exit ends the program typed into the REPL, not the REPL itself
+                            if runtime.exit_requested() {
+                                match runtime.finish() {
+                                    Ok(code) => println!("Program exited with code {}", code),
+                                    Err(error) => runtime.report_error(&error),
+                                }
+                                runtime = RunTime::default();
+                            }
                         }
                     }
                 }
@@ -1129,3 +378,4 @@ fn main() {
         }
     }
 }
+
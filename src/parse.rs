@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+pub(crate) fn split_whitespace_not_in_quotes(s: &str) -> Vec<String> {
+    let mut split = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in s.chars() {
+        if c == '\"' {
+            in_quotes = !in_quotes;
+        }
+        if c == ' ' && !in_quotes {
+            split.push(current);
+            current = String::new();
+        } else {
+            current.push(c);
+        }
+    }
+
+    split.push(current);
+
+    split
+}
+
+//splits the items of a [a, b, c] list literal on commas that are not in quotes
+pub(crate) fn split_list(s: &str) -> Vec<String> {
+    let mut split = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in s.chars() {
+        if c == '\"' {
+            in_quotes = !in_quotes;
+        }
+        if c == ',' && !in_quotes {
+            split.push(current.trim().to_string());
+            current = String::new();
+        } else {
+            current.push(c);
+        }
+    }
+
+    if !current.trim().is_empty() {
+        split.push(current.trim().to_string());
+    }
+
+    split
+}
+
+//a name that cannot be mistaken for a literal: a letter or _ followed by letters, digits and _, and not true, false or null
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let starts_well = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    starts_well && chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && !matches!(name, "true" | "false" | "null")
+}
+
+//replaces every operand that is an alias with the register it refers to, including the register of a field path like alias.x
+pub(crate) fn resolve_aliases(line: &str, aliases: &HashMap<String, String>) -> String {
+    if aliases.is_empty() {
+        return line.to_string();
+    }
+
+    split_whitespace_not_in_quotes(line)
+        .into_iter()
+        .enumerate()
+        .map(|(i, token)| {
+            let base = token.trim_end_matches(',').split('.').next().unwrap_or_default();
+            match aliases.get(base) {
+                Some(reg) if i > 0 => token.replacen(base, reg, 1),
+                _ => token,
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use crate::error::RuntimeError;
+use crate::runtime::RunTime;
+use crate::step::{no_input_error, Status};
+
+#[derive(Default)]
+struct FunctionStats {
+    calls: u64,
+    instructions: u64,
+    inclusive: Duration, //spent in the function and everything it called, recursive calls count once
+    exclusive: Duration, //spent in the function itself
+}
+
+#[derive(Default)]
+struct LineStats {
+    executions: u64,
+    time: Duration,
+}
+
+//a function on the call stack, node is where the stack leading to it is in the call tree
+struct Call {
+    function: String,
+    node: usize,
+    started: Duration, //the profiled time when it was called
+}
+
+//a function called from the stack of its parent, with the time spent in it on exactly that stack
+struct Node {
+    parent: Option<usize>,
+    function: String,
+    time: Duration,
+}
+
+//runs a program measuring the time and instructions spent in every function and source line.
+//Only the steps themselves are timed so the bookkeeping of the profiler does not show up in it
+#[derive(Default)]
+pub struct Profiler {
+    functions: HashMap<String, FunctionStats>,
+    lines: HashMap<(String, usize), LineStats>,
+    nodes: Vec<Node>,
+    children: HashMap<(Option<usize>, String), usize>, //node of a function called from a parent node
+    calls: Vec<Call>, //follows the call frames of the runtime
+    calls_seen: u64, //calls_made of the runtime when the calls were last followed
+    active: HashMap<String, usize>, //how often each function is on the call stack
+    elapsed: Duration, //profiled time so far
+    instructions: u64,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    //steps a started or restored program until it ends, like resume. Returning from a call and the next
+    //iteration of a loop are timed as part of the innermost function but are not instructions
+    pub fn run(&mut self, runtime: &mut RunTime) -> Result<i32, RuntimeError> {
+        runtime.start_budget();
+        runtime.native_calls = Some(Vec::new());
+        let result = self.steps(runtime);
+        runtime.native_calls = None;
+        result
+    }
+
+    fn steps(&mut self, runtime: &mut RunTime) -> Result<i32, RuntimeError> {
+        self.calls_seen = runtime.calls_made;
+        self.follow_calls(runtime);
+        loop {
+            let location = runtime.location();
+            let started = Instant::now();
+            let status = runtime.step();
+            let time = started.elapsed();
+
+            self.elapsed += time;
+            if let Some(call) = self.calls.last() {
+                self.nodes[call.node].time += time;
+                let stats = self.functions.get_mut(&call.function).unwrap();
+                stats.exclusive += time;
+                stats.instructions += location.is_some() as u64;
+            }
+            if let Some(location) = location {
+                self.instructions += 1;
+                let stats = self.lines.entry((location.function, location.line)).or_default();
+                stats.executions += 1;
+                stats.time += time;
+            }
+            for (function, time) in runtime.native_calls.as_mut().unwrap().drain(..) {
+                self.native_call(function, time);
+            }
+            self.follow_calls(runtime);
+
+            match status {
+                Status::Running => {}
+                Status::WaitingForInput => return Err(no_input_error()),
+                Status::Halted(code) => return Ok(code),
+                Status::Errored(error) => return Err(error),
+            }
+        }
+    }
+
+    //pops the calls that returned and pushes the new ones, a step can do several of either when an error unwinds.
+    //Calls are matched to frames by their depth: the frames made since the last step are the innermost ones, everything
+    //below them was already running. Matching by name would take a new call of the same function at the same depth,
+    //like a recursive function called again by a TRY handler, for the one that returned
+    fn follow_calls(&mut self, runtime: &RunTime) {
+        let frames = &runtime.frames;
+        let new = usize::try_from(runtime.calls_made - self.calls_seen).unwrap_or(usize::MAX).min(frames.len());
+        self.calls_seen = runtime.calls_made;
+        let running = frames.len() - new;
+        while self.calls.len() > running {
+            let call = self.calls.pop().unwrap();
+            let active = self.active.get_mut(&call.function).unwrap();
+            *active -= 1;
+            if *active == 0 {
+                self.functions.get_mut(&call.function).unwrap().inclusive += self.elapsed - call.started;
+            }
+        }
+        for frame in &frames[self.calls.len()..] {
+            let node = self.node(&frame.function);
+            self.functions.entry(frame.function.clone()).or_default().calls += 1;
+            *self.active.entry(frame.function.clone()).or_default() += 1;
+            self.calls.push(Call { function: frame.function.clone(), node, started: self.elapsed });
+        }
+    }
+
+    //a native function runs within the step of the instruction calling it and calls nothing itself, so it is a leaf
+    //of the call tree. Its time is taken off the calling function, the line that called it still includes it
+    fn native_call(&mut self, function: String, time: Duration) {
+        if let Some(call) = self.calls.last() {
+            self.nodes[call.node].time = self.nodes[call.node].time.saturating_sub(time);
+            let stats = self.functions.get_mut(&call.function).unwrap();
+            stats.exclusive = stats.exclusive.saturating_sub(time);
+        }
+        let node = self.node(&function);
+        self.nodes[node].time += time;
+        let stats = self.functions.entry(function).or_default();
+        stats.calls += 1;
+        stats.inclusive += time;
+        stats.exclusive += time;
+    }
+
+    //the node of a function called from the innermost call
+    fn node(&mut self, function: &String) -> usize {
+        let parent = self.calls.last().map(|call| call.node);
+        match self.children.get(&(parent, function.clone())) {
+            //a function calling itself stays one frame of the stack, deep recursion would make huge stacks
+            _ if parent.is_some_and(|parent| &self.nodes[parent].function == function) => parent.unwrap(),
+            Some(node) => *node,
+            None => {
+                self.nodes.push(Node { parent, function: function.clone(), time: Duration::ZERO });
+                self.children.insert((parent, function.clone()), self.nodes.len() - 1);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    //the functions and then the source lines, the most time spent first
+    pub fn report(&self) -> String {
+        let mut functions = self.functions.iter().collect::<Vec<_>>();
+        functions.sort_by(|(a_name, a), (b_name, b)| b.exclusive.cmp(&a.exclusive).then(a_name.cmp(b_name)));
+        let mut lines = self.lines.iter().collect::<Vec<_>>();
+        lines.sort_by(|((a_function, a_line), a), ((b_function, b_line), b)| b.time.cmp(&a.time).then(a_line.cmp(b_line)).then(a_function.cmp(b_function)));
+        let width = self.functions.keys().map(|name| name.len()).max().unwrap_or(0).max("Function".len());
+
+        let mut report = format!("Profiled {} instructions in {}ms\n\n", self.instructions, milliseconds(self.elapsed));
+        let _ = writeln!(report, "{:<width$}  {:>8}  {:>12}  {:>12}  {:>12}", "Function", "Calls", "Instructions", "Inclusive ms", "Exclusive ms");
+        for (name, stats) in functions {
+            let _ = writeln!(report, "{:<width$}  {:>8}  {:>12}  {:>12}  {:>12}", name, stats.calls, stats.instructions, milliseconds(stats.inclusive), milliseconds(stats.exclusive));
+        }
+        let _ = writeln!(report, "\n{:>6}  {:<width$}  {:>10}  {:>12}", "Line", "Function", "Executions", "Time ms");
+        for ((function, line), stats) in lines {
+            let _ = writeln!(report, "{:>6}  {:<width$}  {:>10}  {:>12}", line, function, stats.executions, milliseconds(stats.time));
+        }
+        report
+    }
+
+    //one line per call stack with the nanoseconds spent in its innermost function, like main;loop;square 1200.
+    //This is the folded format flame graph tools read, recursive calls of a function to itself are shown as one
+    pub fn folded(&self) -> String {
+        let mut stacks = self.nodes.iter().enumerate().filter(|(_, node)| !node.time.is_zero()).map(|(i, node)| {
+            let mut names = vec![node.function.as_str()];
+            let mut parent = self.nodes[i].parent;
+            while let Some(node) = parent {
+                names.push(&self.nodes[node].function);
+                parent = self.nodes[node].parent;
+            }
+            names.reverse();
+            format!("{} {}", names.join(";"), node.time.as_nanos())
+        }).collect::<Vec<String>>();
+        stacks.sort();
+        stacks.iter().map(|stack| format!("{}\n", stack)).collect()
+    }
+}
+
+fn milliseconds(time: Duration) -> String {
+    format!("{:.3}", time.as_secs_f64() * 1000.0)
+}
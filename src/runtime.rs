@@ -0,0 +1,1996 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::builtins::{NativeFunction, OpenFile, BUILTIN_FUNCTIONS};
+use crate::data::Data;
+use crate::error::{ErrorKind, ExecResult, LoadError, RuntimeError};
+use crate::limits::Limits;
+use crate::parse::{is_identifier, resolve_aliases, split_list, split_whitespace_not_in_quotes};
+use crate::sandbox::Sandbox;
+use crate::step::{LoopKind, LoopState, Phase, Task};
+use crate::streams::{CapturedRun, Input, SharedBuffer, Streams};
+
+type FunctionBody = Vec<String>;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Function {
+    pub(crate) params: Vec<String>, //call-local names bound to the arguments, `fun add a b -> sum`
+    pub(crate) returns: Vec<String>, //call-local names copied into RET0, RET1... when the function returns
+    pub(crate) body: FunctionBody,
+    pub(crate) lines: Vec<usize>, //line number in the source of every line of the body
+}
+
+//one active function call, the saved values are put back when it returns
+pub(crate) struct Frame {
+    pub(crate) function: String,
+    pub(crate) code: Rc<Function>,
+    pub(crate) pc: usize, //index of the next line of the body to execute
+    pub(crate) handlers: Vec<String>, //functions installed with TRY, innermost last
+    pub(crate) saved: Vec<(String, Option<Data>)>, //register or call-local name -> value before the call
+}
+
+//the body is left out, debug would print it again for every call
+impl std::fmt::Debug for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Frame")
+            .field("function", &self.function)
+            .field("pc", &self.pc)
+            .field("handlers", &self.handlers)
+            .field("saved", &self.saved)
+            .finish()
+    }
+}
+
+//instructions that are valid without any operands
+const NO_ARG_INSTRUCTIONS: [&str; 8] = ["BREAK", "CONTINUE", "DUP", "SWAP", "OVER", "DROP", "ENDTRY", "BREAKPOINT"];
+
+//instructions that write to their first operand, used to reject writes to constants
+const WRITE_INSTRUCTIONS: [&str; 22] = [
+    "MOV", "POP", "INC", "DEC", "ADD", "SUB", "MUL", "DIV", "XOR", "SETG", "SETGE", "SETL", "SETLE",
+    "PEEK", "DEPTH", "LDS", "NEW", "GETF", "SETF", "GETI", "LEN", "FOR",
+];
+
+//the register an instruction writes to, None if it does not write to one
+pub(crate) fn written_register(line: &str) -> Option<String> {
+    let split = split_whitespace_not_in_quotes(line);
+    if !WRITE_INSTRUCTIONS.contains(&split[0].to_uppercase().as_str()) {
+        return None;
+    }
+    split.get(1).map(|operand| operand.trim_end_matches(',').split('.').next().unwrap_or_default().to_string())
+}
+
+//amount of R, P and RET registers, can be changed with the .registers directive
+const DEFAULT_REGISTER_COUNT: usize = 13;
+
+//maximum amount of values on the stack, can be changed with the .stack directive
+const DEFAULT_MAX_STACK_SIZE: usize = 1_048_576;
+
+//state of the comparison and input flags, returned by RunTime::flags
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Flags {
+    pub equal: bool,
+    pub greater: bool,
+    pub lesser: bool,
+    pub zero: bool,
+    pub eof: bool, //the end of a file or the input was reached
+    pub error: bool, //an input_ builtin could not parse what was typed
+}
+
+//set by an instruction to unwind the running functions without it being an error
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Signal {
+    Break, //BREAK, stops the innermost loop
+    Continue, //CONTINUE, starts the next iteration of the innermost loop
+    Exit(i32), //the exit builtin, unwinds everything and ends the program
+}
+
+#[derive(Debug)]
+pub struct RunTime {
+    pub(crate) stack: Vec<Data>,
+    pub(crate) max_stack_size: usize,
+    pub(crate) registers: HashMap<String, Data>,
+    pub(crate) aliases: HashMap<String, String>, //alias name -> register, declared with .alias
+    pub(crate) functions: HashMap<String, Rc<Function>>,
+    pub(crate) structs: HashMap<String, Vec<String>>, //struct name -> field names, declared with struct Name { field, field }
+    pub(crate) constants: HashMap<String, Data>, //read-only values declared in the data block
+    pub(crate) frames: Vec<Frame>, //active function calls, innermost last
+    pub(crate) calls_made: u64, //function calls made so far, tells a new call apart from one that was already running
+    pub(crate) tasks: Vec<Task>, //active function calls and loops, innermost last, what step executes
+    pub(crate) phase: Phase,
+    pub(crate) waiting_for_input: bool, //an input builtin is waiting for the host to feed a line
+    pub(crate) entry: String, //function called by run, main unless changed with .entry or --entry
+    pub(crate) exit_value: Data, //what the entry function itself last put in RET0, the exit code when it returns
+    pub(crate) equal_flag: bool,
+    pub(crate) greater_flag: bool,
+    pub(crate) lesser_flag: bool,
+    pub(crate) zero_flag: bool,
+    pub(crate) eof_flag: bool, //set when file_read_line or one of the input builtins reaches the end of the file
+    pub(crate) error_flag: bool, //set when an input_ builtin could not parse what was typed
+    pub(crate) loop_depth: usize, //how many LOOP, LOOPNODEC or FOR instructions are currently executing
+    pub(crate) loop_counters: Vec<i64>, //saved L0 value of every active LOOP / LOOPNODEC, innermost last
+    pub(crate) signal: Option<Signal>, //set by BREAK / CONTINUE / exit, unwinds to the innermost loop or out of the program
+    pub(crate) atexit_handlers: Vec<String>, //functions registered with atexit, run in reverse order when the program ends
+    pub(crate) verbose: bool, //print a message when the program exits
+    pub(crate) sandbox: Sandbox,
+    pub(crate) files: HashMap<usize, OpenFile>, //files opened with file_open by handle
+    pub(crate) next_file_handle: usize,
+    pub(crate) started: Instant, //when the runtime was created, clock measures from here
+    pub(crate) natives: HashMap<String, NativeFunction>, //builtins and functions registered by the host, looked up before user functions
+    pub(crate) native_calls: Option<Vec<(String, Duration)>>, //native functions called and how long they took, only kept while profiling
+    pub(crate) streams: Streams,
+    pub(crate) limits: Limits,
+    pub(crate) instructions_executed: u64,
+    pub(crate) deadline: Option<Instant>, //when the time limit runs out for the current run / execute
+}
+
+//an empty program, what the REPL starts from
+impl Default for RunTime {
+    fn default() -> RunTime {
+        RunTime::new("").unwrap()
+    }
+}
+
+impl RunTime {
+    //loads code into a runtime using the process stdin, stdout and stderr
+    pub fn new(code: &str) -> Result<RunTime, LoadError> {
+        RunTime::load(code, Streams::stdio())
+    }
+
+    //loads the program in the file at path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<RunTime, LoadError> {
+        let path = path.as_ref();
+        let code = read_to_string(path).map_err(|error| LoadError::new(format!("Cannot read {}: {}", path.display(), error)))?;
+        RunTime::new(&code)
+    }
+
+    //loads code into a runtime reading program input from input, printing to output and reporting diagnostics to errors
+    pub fn with_streams<R, W, E>(code: &str, input: R, output: W, errors: E) -> Result<RunTime, LoadError>
+    where
+        R: BufRead + 'static,
+        W: Write + 'static,
+        E: Write + 'static,
+    {
+        RunTime::load(code, Streams { input: Input::Reader(Box::new(input)), output: Box::new(output), errors: Box::new(errors) })
+    }
+
+    //loads code into a runtime whose input is given with feed_input, for hosts that step the program themselves
+    pub fn with_fed_input<W, E>(code: &str, output: W, errors: E) -> Result<RunTime, LoadError>
+    where
+        W: Write + 'static,
+        E: Write + 'static,
+    {
+        let input = Input::Fed { pending: String::new(), closed: false };
+        RunTime::load(code, Streams { input, output: Box::new(output), errors: Box::new(errors) })
+    }
+
+    //runs code with input as its stdin and returns everything it printed, uncaught errors are reported to the captured errors
+    pub fn run_with_input(code: &str, input: &str, args: &[String]) -> Result<CapturedRun, LoadError> {
+        let output = SharedBuffer::default();
+        let errors = SharedBuffer::default();
+        let mut runtime = RunTime::with_streams(code, io::Cursor::new(input.to_string()), output.clone(), errors.clone())?;
+        let result = runtime.run(args);
+        if let Err(error) = &result {
+            runtime.report_error(error);
+        }
+        Ok(CapturedRun { result, output: output.contents(), errors: errors.contents() })
+    }
+
+    fn load(code: &str, streams: Streams) -> Result<RunTime, LoadError> {
+        let mut functions = HashMap::new();
+        let mut structs: HashMap<String, Vec<String>> = HashMap::new();
+        let mut data_block: Vec<(String, String)> = Vec::new(); //constant name -> unparsed value
+        let mut registers: HashMap<String, Data> = HashMap::new();
+        let mut aliases: HashMap<String, String> = HashMap::new();
+        let mut register_count = DEFAULT_REGISTER_COUNT;
+        let mut max_stack_size = DEFAULT_MAX_STACK_SIZE;
+        let mut entry = "main".to_string();
+
+        //find all the functions and directives
+        let mut lines = code.lines().enumerate().filter(|(_, l)| !l.is_empty());
+
+        while let Some((_, line)) = lines.next() {
+            if line.starts_with("fun ") {
+                let split = line.split_whitespace().collect::<Vec<&str>>();
+                let arrow = split.iter().position(|s| *s == "->").unwrap_or(split.len());
+
+                if split.len() < 2 || arrow < 2 || arrow == split.len() - 1 {
+                    return Err(LoadError::new(format!("Invalid function declaration: {}", line)));
+                }
+
+                let name = split[1].to_string();
+                let params = split[2..arrow].iter().map(|p| p.to_string()).collect::<Vec<String>>();
+                let returns = split[(arrow + 1).min(split.len())..].iter().map(|r| r.to_string()).collect::<Vec<String>>();
+                let mut body: Vec<String> = Vec::new();
+                let mut body_lines = Vec::new();
+                for (number, l) in lines.by_ref() {
+                    if l.starts_with("end") {
+                        break;
+                    }
+                    if l.trim_start().starts_with("//") || l.is_empty() { //ignore comments and empty lines
+                        continue;
+                    }
+                    body.push(l.trim_start().to_string());
+                    body_lines.push(number + 1);
+                }
+                functions.insert(name, Function { params, returns, body, lines: body_lines });
+            }
+            else if line.starts_with(".stack") {
+                match line.split_whitespace().nth(1).map(|size| size.parse::<usize>()) {
+                    Some(Ok(size)) => max_stack_size = size,
+                    _ => {
+                        return Err(LoadError::new(format!("Invalid stack size directive: {}", line)));
+                    }
+                }
+            }
+            else if line.starts_with(".registers") {
+                match line.split_whitespace().nth(1).map(|count| count.parse::<usize>()) {
+                    Some(Ok(count)) if count > 0 => register_count = count,
+                    _ => {
+                        return Err(LoadError::new(format!("Invalid register count directive: {}", line)));
+                    }
+                }
+            }
+            else if line.starts_with(".entry") {
+                let split = line.split_whitespace().collect::<Vec<&str>>();
+                if split.len() != 2 {
+                    return Err(LoadError::new(format!("Invalid entry directive: {}", line)));
+                }
+                entry = split[1].to_string();
+            }
+            else if line.starts_with(".alias") {
+                let split = line.split_whitespace().collect::<Vec<&str>>();
+                if split.len() != 3 {
+                    return Err(LoadError::new(format!("Invalid alias declaration: {}", line)));
+                }
+                aliases.insert(split[1].to_string(), split[2].to_string());
+            }
+            else if line.trim_end() == "data" {
+                for (_, l) in lines.by_ref() {
+                    if l.starts_with("end") {
+                        break;
+                    }
+                    let l = l.trim();
+                    if l.starts_with("//") || l.is_empty() {
+                        continue;
+                    }
+                    match l.split_once(char::is_whitespace) {
+                        Some((name, value)) => data_block.push((name.to_string(), value.trim().to_string())),
+                        None => {
+                            return Err(LoadError::new(format!("Invalid constant declaration: {}", l)));
+                        }
+                    }
+                }
+            }
+            else if line.starts_with("struct ") {
+                //the declaration can span multiple lines, it ends at the closing brace
+                let mut declaration = line.to_string();
+                while !declaration.contains('}') {
+                    match lines.next() {
+                        Some((_, l)) => {
+                            declaration.push(' ');
+                            declaration.push_str(l);
+                        }
+                        None => break,
+                    }
+                }
+
+                let (Some(open), Some(close)) = (declaration.find('{'), declaration.find('}')) else {
+                    return Err(LoadError::new(format!("Invalid struct declaration: {}", declaration)));
+                };
+                if close < open {
+                    return Err(LoadError::new(format!("Invalid struct declaration: {}", declaration)));
+                }
+                let name = declaration["struct ".len()..open].trim().to_string();
+                let fields = declaration[open + 1..close]
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|field| !field.is_empty())
+                    .map(|field| field.to_string())
+                    .collect::<Vec<String>>();
+                if name.is_empty() || name.contains(' ') {
+                    return Err(LoadError::new(format!("Invalid struct declaration: {}", declaration)));
+                }
+                structs.insert(name, fields);
+            }
+        }
+
+        //initialize registers
+        for i in 0..register_count {
+            registers.insert(format!("R{}", i), Data::Null); //general purpose registers rX
+            registers.insert(format!("P{}", i), Data::Null); //parameter registers pX
+            registers.insert(format!("RET{}", i), Data::Null); //return registers (for functions) retX
+        }
+        registers.insert(format!("L{}", 0), Data::Null); //loop register, determines how many the loop instruction should run
+        registers.insert("SP".to_string(), Data::Int(0)); //stack pointer, the current depth of the stack
+        registers.insert("FP".to_string(), Data::Int(0)); //frame pointer, the depth of the stack when the current function was called
+        registers.insert("ARGC".to_string(), Data::Int(0)); //amount of program arguments passed after the file name
+        registers.insert("ARGV".to_string(), Data::List(Vec::new())); //the program arguments as a list of strings
+
+        //aliases are replaced by the registers they name before anything runs
+        for (alias, reg) in &aliases {
+            if !registers.contains_key(reg) {
+                return Err(LoadError::new(format!("Alias {} refers to non-existant register: {}", alias, reg)));
+            }
+            if registers.contains_key(alias) || functions.contains_key(alias) || BUILTIN_FUNCTIONS.iter().any(|(builtin, _)| builtin == alias) {
+                return Err(LoadError::new(format!("Alias {} shadows an existing register or function", alias)));
+            }
+        }
+        for (name, function) in &functions {
+            for local in function.params.iter().chain(&function.returns) {
+                if registers.contains_key(local) {
+                    return Err(LoadError::new(format!("Parameter {} of function {} shadows a register", local, name)));
+                }
+                if functions.contains_key(local) || BUILTIN_FUNCTIONS.iter().any(|(builtin, _)| builtin == local) || aliases.contains_key(local) {
+                    return Err(LoadError::new(format!("Parameter {} of function {} shadows a function or alias", local, name)));
+                }
+            }
+        }
+        for function in functions.values_mut() {
+            for line in function.body.iter_mut() {
+                *line = resolve_aliases(line, &aliases);
+            }
+        }
+
+        let mut runtime = RunTime {
+            stack: Vec::new(),
+            max_stack_size,
+            registers,
+            aliases,
+            functions: functions.into_iter().map(|(name, function)| (name, Rc::new(function))).collect(),
+            structs,
+            constants: HashMap::new(),
+            frames: Vec::new(),
+            calls_made: 0,
+            tasks: Vec::new(),
+            phase: Phase::Idle,
+            waiting_for_input: false,
+            entry,
+            exit_value: Data::Null,
+            equal_flag: false,
+            greater_flag: false,
+            lesser_flag: false,
+            zero_flag: false,
+            eof_flag: false,
+            error_flag: false,
+            loop_depth: 0,
+            loop_counters: Vec::new(),
+            signal: None,
+            atexit_handlers: Vec::new(),
+            verbose: false,
+            sandbox: Sandbox::default(),
+            files: HashMap::new(),
+            next_file_handle: 0,
+            started: Instant::now(),
+            natives: HashMap::new(),
+            native_calls: None,
+            streams,
+            limits: Limits::default(),
+            instructions_executed: 0,
+            deadline: None,
+        };
+        for (name, function) in BUILTIN_FUNCTIONS {
+            runtime.register_native(name, function);
+        }
+        runtime.load_constants(data_block)?;
+        runtime.verify_constant_writes()?;
+        Ok(runtime)
+    }
+
+    //makes a Rust function callable from scripts with JMP / CALL under name, replacing any builtin or native of the same name.
+    //it takes its arguments from P0, P1... and returns in RET0, RET1..., errors it returns can be caught with TRY
+    pub fn register_native<F>(&mut self, name: &str, function: F)
+    where
+        F: Fn(&mut RunTime) -> ExecResult + 'static,
+    {
+        self.natives.insert(name.to_string(), NativeFunction(Rc::new(function)));
+    }
+
+    //function run by run, main unless the program changed it with .entry
+    pub fn set_entry(&mut self, name: &str) {
+        self.entry = name.to_string();
+    }
+
+    //changes the amount of R, P and RET registers the program was loaded with, overriding its .registers directive
+    pub fn set_register_count(&mut self, count: usize) -> Result<(), LoadError> {
+        if count == 0 {
+            return Err(LoadError::new("Invalid register count: 0".to_string()));
+        }
+        let added = (0..count).flat_map(|i| [format!("R{}", i), format!("P{}", i), format!("RET{}", i)]).filter(|reg| !self.registers.contains_key(reg)).collect::<Vec<String>>();
+        for reg in &added {
+            if self.constants.contains_key(reg) {
+                return Err(LoadError::new(format!("Constant {} shadows an existing register, alias or function", reg)));
+            }
+            if let Some((name, _)) = self.functions.iter().find(|(_, function)| function.params.contains(reg) || function.returns.contains(reg)) {
+                return Err(LoadError::new(format!("Parameter {} of function {} shadows a register", reg, name)));
+            }
+        }
+        //R, P or RET followed by a number that is not below count
+        let removed = |reg: &str| {
+            let number = reg.strip_prefix("RET").or_else(|| reg.strip_prefix('R')).or_else(|| reg.strip_prefix('P'));
+            number.and_then(|number| number.parse::<usize>().ok()).is_some_and(|number| number >= count)
+        };
+        if let Some((alias, reg)) = self.aliases.iter().find(|(_, reg)| removed(reg)) {
+            return Err(LoadError::new(format!("Alias {} refers to non-existant register: {}", alias, reg)));
+        }
+
+        self.registers.retain(|reg, _| !removed(reg));
+        for reg in added {
+            self.registers.insert(reg, Data::Null);
+        }
+        Ok(())
+    }
+
+    //print a message when the program is ended by exit
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
+
+    //lets the file builtins read anywhere inside dir
+    pub fn allow_read(&mut self, dir: &str) -> Result<(), String> {
+        Sandbox::allow(&mut self.sandbox.read, dir)
+    }
+
+    //lets the file builtins write anywhere inside dir
+    pub fn allow_write(&mut self, dir: &str) -> Result<(), String> {
+        Sandbox::allow(&mut self.sandbox.write, dir)
+    }
+
+    //lets the exec builtin run programs
+    pub fn allow_exec(&mut self, allow: bool) {
+        self.sandbox.exec = allow;
+    }
+
+    //value of a register, call-local name or alias, None if there is no such register
+    pub fn register(&self, name: &str) -> Option<&Data> {
+        let name = self.aliases.get(name).map_or(name, |reg| reg.as_str());
+        self.registers.get(name)
+    }
+
+    pub fn set_register(&mut self, name: &str, value: Data) -> ExecResult {
+        let name = self.aliases.get(name).map_or(name, |reg| reg.as_str()).to_string();
+        if !self.is_register(&name) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("Attempted to set non-existant register: {}", name)));
+        }
+        self.registers.insert(name, value);
+        Ok(())
+    }
+
+    //the stack, bottom first
+    pub fn stack(&self) -> &[Data] {
+        &self.stack
+    }
+
+    pub fn push_stack(&mut self, value: Data) -> ExecResult {
+        self.stack_push(value, "push")
+    }
+
+    pub fn flags(&self) -> Flags {
+        Flags {
+            equal: self.equal_flag,
+            greater: self.greater_flag,
+            lesser: self.lesser_flag,
+            zero: self.zero_flag,
+            eof: self.eof_flag,
+            error: self.error_flag,
+        }
+    }
+
+    //executes a single line as if it was part of a function, like the REPL does. .alias lines declare an alias
+    pub fn execute(&mut self, line: &str) -> ExecResult {
+        if line.trim_start().starts_with(".alias") {
+            return self.add_alias(line).map_err(|error| RuntimeError::new(ErrorKind::Instruction, error.message));
+        }
+        let line = resolve_aliases(line.trim(), &self.aliases);
+        self.start_budget();
+        let base = self.tasks.len();
+        self.execute_line(&line)?;
+        self.run_tasks(base)
+    }
+
+    //whether the exit builtin ended the program, call finish to run the exit handlers
+    pub fn exit_requested(&self) -> bool {
+        matches!(self.signal, Some(Signal::Exit(_)))
+    }
+
+    fn load_constants(&mut self, data_block: Vec<(String, String)>) -> Result<(), LoadError> {
+        for (name, value) in data_block {
+            if !is_identifier(&name) {
+                return Err(LoadError::new(format!("Invalid constant name: {}", name)));
+            }
+            if self.is_register(&name) || self.functions.contains_key(&name) || self.natives.contains_key(&name) || self.aliases.contains_key(&name) {
+                return Err(LoadError::new(format!("Constant {} shadows an existing register, alias or function", name)));
+            }
+            if let Some((function, _)) = self.functions.iter().find(|(_, function)| function.params.contains(&name) || function.returns.contains(&name)) {
+                return Err(LoadError::new(format!("Parameter {} of function {} shadows a constant", name, function)));
+            }
+
+            //earlier constants can be used in the values of later ones
+            let value = if value.starts_with('[') && value.ends_with(']') {
+                split_list(&value[1..value.len() - 1]).iter().map(|item| self.determine_type(item)).collect::<Result<Vec<Data>, RuntimeError>>().map(Data::List)
+            } else {
+                self.determine_type(&value)
+            };
+            match value {
+                Ok(value) => self.constants.insert(name, value),
+                Err(error) => {
+                    return Err(LoadError::new(format!("Invalid value for constant {}: {}", name, error.message)));
+                }
+            };
+        }
+        Ok(())
+    }
+
+    fn verify_constant_writes(&self) -> Result<(), LoadError> {
+        for (name, function) in &self.functions {
+            for line in &function.body {
+                let split = split_whitespace_not_in_quotes(line);
+                let instruction = split[0].to_uppercase();
+                let written = match instruction.as_str() {
+                    "LOCAL" => split[1..].to_vec(),
+                    _ if WRITE_INSTRUCTIONS.contains(&instruction.as_str()) => split.get(1).cloned().into_iter().collect(),
+                    _ => Vec::new(),
+                };
+                for operand in written {
+                    let target = operand.trim_end_matches(',').split('.').next().unwrap();
+                    if self.constants.contains_key(target) {
+                        return Err(LoadError::new(format!("Attempted to write to constant {} in function {}: {}", target, name, line)));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    //.alias name register
+    pub fn add_alias(&mut self, line: &str) -> Result<(), LoadError> {
+        let split = line.split_whitespace().collect::<Vec<&str>>();
+        if split.len() != 3 {
+            return Err(LoadError::new(format!("Invalid alias declaration: {}", line)));
+        }
+        if !self.is_register(&split[2].to_string()) {
+            return Err(LoadError::new(format!("Alias {} refers to non-existant register: {}", split[1], split[2])));
+        }
+        if self.is_register(&split[1].to_string()) || self.functions.contains_key(split[1]) || self.natives.contains_key(split[1]) {
+            return Err(LoadError::new(format!("Alias {} shadows an existing register or function", split[1])));
+        }
+        self.aliases.insert(split[1].to_string(), split[2].to_string());
+        Ok(())
+    }
+
+    pub(crate) fn is_register(&self, reg: &String) -> bool {
+        self.registers.contains_key(reg)
+    }
+
+    fn determine_type(&self, data: &String) -> Result<Data, RuntimeError> {
+        if let Some(constant) = self.constants.get(data) {
+            Ok(constant.clone())
+        } else if data.starts_with('\"') && data.ends_with('\"') {
+            Ok(Data::String(data[1..data.len() - 1].to_string()))
+        } else if let Some(name) = data.strip_prefix('&') {
+            if !self.natives.contains_key(name) && !self.functions.contains_key(name) {
+                return Err(RuntimeError::new(ErrorKind::Function, format!("Reference to non-existant function: {}", data)));
+            }
+            Ok(Data::Function(name.to_string()))
+        } else if data == "true" || data == "false" {
+            Ok(Data::Bool(data == "true"))
+        } else if data.contains('.') && data.parse::<f64>().is_ok() {
+            Ok(Data::Float(data.parse::<f64>().unwrap()))
+        } else if data.parse::<i64>().is_ok() {
+            Ok(Data::Int(data.parse::<i64>().unwrap()))
+        }
+        else {
+            Err(RuntimeError::new(ErrorKind::Type, format!("Unknown data type: {}", data)))
+        }
+    }
+
+    pub(crate) fn value_of(&self, data: &String) -> Result<Data, RuntimeError> {
+        if self.is_register(data) {
+            Ok(self.registers.get(data).unwrap().clone())
+        } else {
+            self.determine_type(data)
+        }
+    }
+
+    pub(crate) fn execute_line(&mut self, line: &str) -> ExecResult {
+        self.count_instruction()?;
+        let split = split_whitespace_not_in_quotes(line);
+        let instruction = split[0].trim().to_uppercase();
+        if split.len() < 2 && !NO_ARG_INSTRUCTIONS.contains(&instruction.as_str()) {
+            return Err(RuntimeError::new(ErrorKind::Instruction, format!("Invalid instruction: {}", line)));
+        }
+
+        let first_arg = split.get(1).map(|arg| arg.replace(',', "")).unwrap_or_default();
+        let second_arg = || match split.get(2) {
+            Some(arg) => Ok(arg.to_string()),
+            None => Err(RuntimeError::new(ErrorKind::Instruction, format!("Missing operand: {}", line))),
+        };
+
+        let result = match instruction.as_str() {
+            "MOV" => self.mov(first_arg.clone(), second_arg()?),
+            "PUSH" => self.push(first_arg.clone()),
+            "POP" => self.pop(first_arg.clone()),
+            "DUP" => self.dup(),
+            "SWAP" => self.swap(),
+            "OVER" => self.over(),
+            "DROP" => self.drop(),
+            "PEEK" => self.peek(&first_arg, &second_arg()?),
+            "DEPTH" => self.depth(&first_arg),
+            "LDS" => self.lds(&first_arg, &second_arg()?),
+            "STS" => self.sts(&first_arg, &second_arg()?),
+            "INC" => self.inc(&first_arg),
+            "DEC" => self.dec(&first_arg),
+            "SUB" => self.sub(&first_arg, &second_arg()?),
+            "ADD" => self.add(&first_arg, &second_arg()?),
+            "DIV" => self.div(&first_arg, &second_arg()?),
+            "MUL" => self.mul(&first_arg, &second_arg()?),
+            "CMP" => self.cmp(&first_arg, &second_arg()?),
+            "JMP" => self.jmp(&first_arg),
+            "CALLR" => self.callr(&first_arg),
+            "CALL" => {
+                let args = split[2..].iter().map(|arg| arg.trim_end_matches(',').to_string()).collect::<Vec<String>>();
+                self.call(&first_arg, &args)
+            }
+            "LOCAL" => {
+                let regs = split[1..].iter().map(|reg| reg.trim_end_matches(',').to_string()).collect::<Vec<String>>();
+                self.local(&regs)
+            }
+            "JE" => self.je(&first_arg),
+            "JNE" => self.jne(&first_arg),
+            "JG" => self.jg(&first_arg),
+            "JGE" => self.jge(&first_arg),
+            "JL" => self.jl(&first_arg),
+            "JLE" => self.jle(&first_arg),
+            "JZ" => self.jz(&first_arg),
+            "JNZ" => self.jnz(&first_arg),
+            "JEOF" => self.jeof(&first_arg),
+            "JNEOF" => self.jneof(&first_arg),
+            "JERR" => self.jerr(&first_arg),
+            "JNERR" => self.jnerr(&first_arg),
+            "SETG" => self.setg(&first_arg),
+            "SETGE" => self.setge(&first_arg),
+            "SETL" => self.setl(&first_arg),
+            "SETLE" => self.setle(&first_arg),
+            "XOR" => self.xor(&first_arg, &second_arg()?),
+            "LOOP" => self.loop_(&first_arg),
+            "LOOPNODEC" => self.loop_no_dec(&first_arg),
+            "FOR" => {
+                if split.len() != 6 {
+                    return Err(RuntimeError::new(ErrorKind::Instruction, format!("[for] Expected FOR register, start, end, step, label: {}", line)));
+                }
+                let args = split[2..].iter().map(|arg| arg.replace(',', "")).collect::<Vec<String>>();
+                self.for_(&first_arg, &args[0], &args[1], &args[2], &args[3])
+            }
+            "BREAK" => self.break_(),
+            "BREAKPOINT" => Ok(()), //only pauses the debugger
+            "CONTINUE" => self.continue_(),
+            "TRY" => self.try_(&first_arg),
+            "ENDTRY" => self.end_try(),
+            "THROW" => self.throw(&first_arg),
+            "NEW" => self.new_struct(&first_arg, &second_arg()?),
+            "GETF" => self.getf(&first_arg, &second_arg()?),
+            "SETF" => self.setf(&first_arg, &second_arg()?),
+            "GETI" => {
+                let index = split.get(3).map(|arg| arg.to_string());
+                match index {
+                    Some(index) => self.geti(&first_arg, &second_arg()?.replace(',', ""), &index),
+                    None => Err(RuntimeError::new(ErrorKind::Instruction, format!("Missing operand: {}", line))),
+                }
+            }
+            "LEN" => self.len(&first_arg, &second_arg()?),
+
+            _ => Err(RuntimeError::new(ErrorKind::Instruction, format!("Unknown command: {}", split[0]))),
+        };
+
+        //values only grow through the register an instruction writes to, native functions and
+        //returning from a call check what they wrote themselves
+        if result.is_ok() && self.limits.value_size.is_some() && WRITE_INSTRUCTIONS.contains(&instruction.as_str()) {
+            self.check_value_size(&first_arg)?;
+        }
+        result
+    }
+
+    fn update_stack_pointer(&mut self) {
+        self.registers.insert("SP".to_string(), Data::Int(self.stack.len() as i64));
+    }
+
+    //the host's stack limit is checked first, so the program's .stack size only decides when it is the smaller one
+    fn stack_push(&mut self, data: Data, instruction: &str) -> ExecResult {
+        self.check_stack_depth()?;
+        self.check_pushed_size(&data)?;
+        if self.stack.len() >= self.max_stack_size {
+            return Err(RuntimeError::new(ErrorKind::StackOverflow, format!("[{}] Stack overflow, exceeded maximum stack size of {}", instruction, self.max_stack_size)));
+        }
+        self.stack.push(data);
+        self.update_stack_pointer();
+        Ok(())
+    }
+
+    fn stack_pop(&mut self, instruction: &str) -> Result<Data, RuntimeError> {
+        if let Some(data) = self.stack.pop() {
+            self.update_stack_pointer();
+            Ok(data)
+        }
+        else {
+            Err(RuntimeError::new(ErrorKind::Stack, format!("[{}] Attempted to pop from empty stack", instruction)))
+        }
+    }
+
+    //index of the value `offset` slots away from the frame pointer, arguments pushed by the caller are at negative offsets
+    fn frame_index(&self, offset: &String, instruction: &str) -> Result<usize, RuntimeError> {
+        let (Data::Int(fp), Data::Int(offset)) = (self.registers.get("FP").unwrap().clone(), self.value_of(offset)?) else {
+            return Err(RuntimeError::new(ErrorKind::Stack, format!("[{}] Attempted to address the stack with a non-integer offset", instruction)));
+        };
+        let index = fp.saturating_add(offset);
+        if index < 0 || index as usize >= self.stack.len() {
+            return Err(RuntimeError::new(ErrorKind::Stack, format!("[{}] Stack offset {} is out of bounds (FP = {}, SP = {})", instruction, offset, fp, self.stack.len())));
+        }
+        Ok(index as usize)
+    }
+
+    fn push(&mut self, data: String) -> ExecResult {
+        let data = self.value_of(&data)?;
+        self.stack_push(data, "push")
+    }
+
+    fn pop(&mut self, out_reg: String) -> ExecResult {
+        if !self.is_register(&out_reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[pop] Attempted to pop into non-existant register: {}", out_reg)));
+        }
+
+        let data = self.stack_pop("pop")?;
+        self.registers.insert(out_reg, data);
+        Ok(())
+    }
+
+    fn dup(&mut self) -> ExecResult {
+        match self.stack.last() {
+            Some(data) => self.stack_push(data.clone(), "dup"),
+            None => Err(RuntimeError::new(ErrorKind::Stack, "[dup] Attempted to duplicate the top of an empty stack".to_string())),
+        }
+    }
+
+    fn swap(&mut self) -> ExecResult {
+        if self.stack.len() < 2 {
+            return Err(RuntimeError::new(ErrorKind::Stack, "[swap] Attempted to swap with less than 2 values on the stack".to_string()));
+        }
+        let len = self.stack.len();
+        self.stack.swap(len - 1, len - 2);
+        Ok(())
+    }
+
+    fn over(&mut self) -> ExecResult {
+        if self.stack.len() < 2 {
+            return Err(RuntimeError::new(ErrorKind::Stack, "[over] Attempted to copy the second value with less than 2 values on the stack".to_string()));
+        }
+        let data = self.stack[self.stack.len() - 2].clone();
+        self.stack_push(data, "over")
+    }
+
+    fn drop(&mut self) -> ExecResult {
+        self.stack_pop("drop")?;
+        Ok(())
+    }
+
+    fn peek(&mut self, reg: &String, depth: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[peek] Attempted to peek into non-existant register: {}", reg)));
+        }
+
+        match self.value_of(depth)? {
+            Data::Int(n) if n >= 0 && (n as usize) < self.stack.len() => {
+                let data = self.stack[self.stack.len() - 1 - n as usize].clone();
+                self.registers.insert(reg.to_string(), data);
+            }
+            Data::Int(n) => {
+                return Err(RuntimeError::new(ErrorKind::Stack, format!("[peek] Attempted to peek {} values deep into a stack of {} values", n, self.stack.len())));
+            }
+            _ => {
+                return Err(RuntimeError::new(ErrorKind::Type, format!("[peek] Attempted to peek with non-integer depth: {}", depth)));
+            }
+        }
+        Ok(())
+    }
+
+    fn depth(&mut self, reg: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[depth] Attempted to store the stack depth in non-existant register: {}", reg)));
+        }
+        self.registers.insert(reg.to_string(), Data::Int(self.stack.len() as i64));
+        Ok(())
+    }
+
+    fn lds(&mut self, reg: &String, offset: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[lds] Attempted to load into non-existant register: {}", reg)));
+        }
+        let index = self.frame_index(offset, "lds")?;
+        self.registers.insert(reg.to_string(), self.stack[index].clone());
+        Ok(())
+    }
+
+    fn sts(&mut self, offset: &String, data: &String) -> ExecResult {
+        let index = self.frame_index(offset, "sts")?;
+        self.stack[index] = self.value_of(data)?;
+        Ok(())
+    }
+
+    fn mov(&mut self, reg: String, data: String) -> ExecResult {
+        if !self.is_register(&reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[mov] Attempted to move data into a non-existant register: {}", reg)));
+        }
+        
+        if self.is_register(&data) { //move register to register
+            self.registers.insert(reg, self.registers.get(&data).unwrap().clone());
+        }
+        else { //move hardcoded data to register
+            self.registers.insert(reg, self.determine_type(&data)?);
+        }
+        Ok(())
+    }
+
+    fn new_struct(&mut self, reg: &String, name: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[new] Attempted to create struct in non-existant register: {}", reg)));
+        }
+        let Some(fields) = self.structs.get(name) else {
+            return Err(RuntimeError::new(ErrorKind::Type, format!("[new] Attempted to create non-existant struct: {}", name)));
+        };
+
+        let fields = fields.iter().map(|field| (field.to_string(), Data::Null)).collect();
+        self.registers.insert(reg.to_string(), Data::Struct(name.to_string(), fields));
+        Ok(())
+    }
+
+    //splits `obj.field.field` into the register and the path of fields
+    fn field_path(&self, path: &String, instruction: &str) -> Result<(String, Vec<String>), RuntimeError> {
+        let mut split = path.split('.');
+        let reg = split.next().unwrap().to_string();
+        let fields = split.map(|field| field.to_string()).collect::<Vec<String>>();
+        if fields.is_empty() {
+            return Err(RuntimeError::new(ErrorKind::Field, format!("[{}] Expected register.field, got: {}", instruction, path)));
+        }
+        if !self.is_register(&reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[{}] Attempted to access field of non-existant register: {}", instruction, reg)));
+        }
+        Ok((reg, fields))
+    }
+
+    fn getf(&mut self, reg: &String, path: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[getf] Attempted to get field into non-existant register: {}", reg)));
+        }
+
+        let (obj, fields) = self.field_path(path, "getf")?;
+        let mut value = self.registers.get(&obj).unwrap();
+        for field in &fields {
+            let Data::Struct(name, values) = value else {
+                return Err(RuntimeError::new(ErrorKind::Type, format!("[getf] Attempted to get field {} of non-struct value: {}", field, path)));
+            };
+            match values.iter().find(|(f, _)| f == field) {
+                Some((_, v)) => value = v,
+                None => return Err(RuntimeError::new(ErrorKind::Field, format!("[getf] Struct {} has no field {}", name, field))),
+            }
+        }
+
+        self.registers.insert(reg.to_string(), value.clone());
+        Ok(())
+    }
+
+    fn setf(&mut self, path: &String, data: &String) -> ExecResult {
+        let (obj, fields) = self.field_path(path, "setf")?;
+        let data = self.value_of(data)?;
+
+        let mut value = self.registers.get_mut(&obj).unwrap();
+        for field in &fields {
+            let Data::Struct(name, values) = value else {
+                return Err(RuntimeError::new(ErrorKind::Type, format!("[setf] Attempted to set field {} of non-struct value: {}", field, path)));
+            };
+            match values.iter_mut().find(|(f, _)| f == field) {
+                Some((_, v)) => value = v,
+                None => return Err(RuntimeError::new(ErrorKind::Field, format!("[setf] Struct {} has no field {}", name, field))),
+            }
+        }
+
+        *value = data;
+        Ok(())
+    }
+
+    fn geti(&mut self, reg: &String, data: &String, index: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[geti] Attempted to get item into non-existant register: {}", reg)));
+        }
+
+        let Data::Int(i) = self.value_of(index)? else {
+            return Err(RuntimeError::new(ErrorKind::Type, format!("[geti] Attempted to index with non-integer value: {}", index)));
+        };
+        let item = match self.value_of(data)? {
+            Data::List(items) if i >= 0 && (i as usize) < items.len() => items[i as usize].clone(),
+            Data::String(s) if i >= 0 && (i as usize) < s.chars().count() => Data::String(s.chars().nth(i as usize).unwrap().to_string()),
+            Data::List(_) | Data::String(_) => {
+                return Err(RuntimeError::new(ErrorKind::Argument, format!("[geti] Index {} is out of bounds for: {}", i, data)));
+            }
+            _ => {
+                return Err(RuntimeError::new(ErrorKind::Type, format!("[geti] Attempted to index non-list / non-string value: {}", data)));
+            }
+        };
+        self.registers.insert(reg.to_string(), item);
+        Ok(())
+    }
+
+    fn len(&mut self, reg: &String, data: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[len] Attempted to store length in non-existant register: {}", reg)));
+        }
+
+        let len = match self.value_of(data)? {
+            Data::List(items) => items.len(),
+            Data::String(s) => s.chars().count(),
+            _ => {
+                return Err(RuntimeError::new(ErrorKind::Type, format!("[len] Attempted to get length of non-list / non-string value: {}", data)));
+            }
+        };
+        self.registers.insert(reg.to_string(), Data::Int(len as i64));
+        Ok(())
+    }
+
+    fn inc(&mut self, reg: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[inc] Attempted to increment non-existant register: {}", reg)));
+        }
+
+        let data = self.registers.get(reg).unwrap();
+        match data {
+            Data::Int(i) => {
+                let result = i.checked_add(1).ok_or_else(|| overflow("inc"))?;
+                self.zero_flag = result == 0;
+                self.registers.insert(reg.to_string(), Data::Int(result));
+
+            },
+            Data::Float(f) => {
+                self.zero_flag = f + 1.0 == 0.0;
+                self.registers.insert(reg.to_string(), Data::Float(f + 1.0));
+            },
+            _ => {
+                return Err(RuntimeError::new(ErrorKind::Type, format!("[inc] Attempted to increment non-numeric register: {}", reg)));
+            }
+        };
+        Ok(())
+    }
+
+    fn dec(&mut self, reg: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[dec] Attempted to decrement non-existant register: {}", reg)));
+        }
+
+        let data = self.registers.get(reg).unwrap();
+        match data {
+            Data::Int(i) => {
+                let result = i.checked_sub(1).ok_or_else(|| overflow("dec"))?;
+                self.zero_flag = result == 0;
+                self.registers.insert(reg.to_string(), Data::Int(result));
+            },
+            Data::Float(f) => {
+                self.zero_flag = f - 1.0 == 0.0;
+                self.registers.insert(reg.to_string(), Data::Float(f - 1.0));
+            },
+            _ => {
+                return Err(RuntimeError::new(ErrorKind::Type, format!("[dec] Attempted to decrement non-numeric register: {}", reg)));
+            }
+        };
+        Ok(())
+    }
+
+    fn add(&mut self, reg: &String, data: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[add] Attempted to add to non-existant register: {}", reg)));
+        }
+
+        if self.is_register(data) {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.registers.get(data).unwrap();
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            let result = i.checked_add(*j).ok_or_else(|| overflow("add"))?;
+                            self.zero_flag = result == 0;
+                            self.registers.insert(reg.to_string(), Data::Int(result));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = *i as f64 + j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 + j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[add] Attempted to add non-numeric data to register: {}", reg)));
+                        }
+                    };
+                }
+
+                Data::Float(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.zero_flag = i + *j as f64 == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i + *j as f64));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = i + j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i + j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[add] Attempted to add non-numeric data to register: {}", reg)));
+                        }
+                    };
+                }
+
+                Data::String(i) => {
+                    match data_data {
+                        Data::String(j) => self.registers.insert(reg.to_string(), Data::String(i.to_string() + j)),
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[add] Attempted to add non-string data to register: {}", reg)));
+                        }
+                    };
+                }
+
+                _ => {
+                    return Err(RuntimeError::new(ErrorKind::Type, format!("[add] Attempted to add to non-numeric / non-string register: {}", reg)));
+                }
+            }
+        }
+        else {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.determine_type(data)?;
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            let result = i.checked_add(j).ok_or_else(|| overflow("add"))?;
+                            self.zero_flag = result == 0;
+                            self.registers.insert(reg.to_string(), Data::Int(result));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = *i as f64 + j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 + j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[add] Attempted to add non-numeric data to register: {}", reg)));
+                        }
+                    };
+                }
+
+                Data::Float(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.zero_flag = i + j as f64 == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i + j as f64));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = i + j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i + j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[add] Attempted to add non-numeric data to register: {}", reg)));
+                        }
+                    };
+                }
+
+                _ => {
+                    return Err(RuntimeError::new(ErrorKind::Type, format!("[add] Attempted to add to non-numeric register: {}", reg)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn sub(&mut self, reg: &String, data: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[sub] Attempted to subtract from non-existant register: {}", reg)));
+        }
+
+        if self.is_register(data) {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.registers.get(data).unwrap();
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            let result = i.checked_sub(*j).ok_or_else(|| overflow("sub"))?;
+                            self.zero_flag = result == 0;
+                            self.registers.insert(reg.to_string(), Data::Int(result));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = *i as f64 - j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 - j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[sub] Attempted to subtract non-numeric data from register: {}", reg)));
+                        }
+                    };
+                }
+
+                Data::Float(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.zero_flag = i - *j as f64 == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i - *j as f64));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = i - j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i - j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[sub] Attempted to subtract non-numeric data from register: {}", reg)));
+                        }
+                    };
+                }
+
+                _ => {
+                    return Err(RuntimeError::new(ErrorKind::Type, format!("[sub] Attempted to subtract from non-numeric register: {}", reg)));
+                }
+            }
+        }
+        else {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.determine_type(data)?;
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            let result = i.checked_sub(j).ok_or_else(|| overflow("sub"))?;
+                            self.zero_flag = result == 0;
+                            self.registers.insert(reg.to_string(), Data::Int(result));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = *i as f64 - j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 - j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[sub] Attempted to subtract non-numeric data from register: {}", reg)));
+                        }
+                    };
+                }
+
+                Data::Float(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.zero_flag = i - j as f64 == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i - j as f64));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = i - j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i - j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[sub] Attempted to subtract non-numeric data from register: {}", reg)));
+                        }
+                    };
+                }
+
+                _ => {
+                    return Err(RuntimeError::new(ErrorKind::Type, format!("[sub] Attempted to subtract from non-numeric register: {}", reg)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn div(&mut self, reg: &String, data: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[div] Attempted to divide non-existant register: {}", reg)));
+        }
+
+        if self.is_register(data) {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.registers.get(data).unwrap();
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            if *j == 0 {
+                                return Err(RuntimeError::new(ErrorKind::DivideByZero, "[div] Attempted to divide by zero.".to_string()));
+                            }
+                            else {
+                                self.zero_flag = i.checked_div(*j) == Some(0);
+                                self.registers.insert(reg.to_string(), Data::Float(*i as f64 / *j as f64));
+                            }
+                        }
+                        Data::Float(j) => {
+                            if *j == 0.0 {
+                                return Err(RuntimeError::new(ErrorKind::DivideByZero, "[div] Attempted to divide by zero.".to_string()));
+                            }
+                            else {
+                                self.zero_flag = *i as f64 / j == 0.0;
+                                self.registers.insert(reg.to_string(), Data::Float(*i as f64 / j));
+                            }
+                        }
+
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[div] Attempted to divide non-numeric data from register: {}", reg)));
+                        }
+                    }
+                }
+
+                Data::Float(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            if *j == 0 {
+                                return Err(RuntimeError::new(ErrorKind::DivideByZero, "[div] Attempted to divide by zero.".to_string()));
+                            }
+                            else {
+                                self.zero_flag = i / *j as f64 == 0.0;
+                                self.registers.insert(reg.to_string(), Data::Float(i / *j as f64));
+                            }
+                        }
+                        Data::Float(j) => {
+                            if *j == 0.0 {
+                                return Err(RuntimeError::new(ErrorKind::DivideByZero, "[div] Attempted to divide by zero.".to_string()));
+                            }
+                            else {
+                                self.zero_flag = i / j == 0.0;
+                                self.registers.insert(reg.to_string(), Data::Float(i / j));
+                            }
+                        }
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[div] Attempted to divide non-numeric data from register: {}", reg)));
+                        }
+                    }
+                }
+
+                _ => {
+                    return Err(RuntimeError::new(ErrorKind::Type, format!("[div] Attempted to divide from non-numeric register: {}", reg)));
+                }
+            }
+        }
+
+        else {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.determine_type(data)?;
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            if j == 0 {
+                                return Err(RuntimeError::new(ErrorKind::DivideByZero, "[div] Attempted to divide by zero.".to_string()));
+                            }
+                            else {
+                                self.zero_flag = i.checked_div(j) == Some(0);
+                                self.registers.insert(reg.to_string(), Data::Float(*i as f64 / j as f64));
+                            }
+                        }
+                        Data::Float(j) => {
+                            if j == 0.0 {
+                                return Err(RuntimeError::new(ErrorKind::DivideByZero, "[div] Attempted to divide by zero.".to_string()));
+                            }
+                            else {
+                                self.zero_flag = *i as f64 / j == 0.0;
+                                self.registers.insert(reg.to_string(), Data::Float(*i as f64 / j));
+                            }
+                        }
+
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[div] Attempted to divide non-numeric data from register: {}", reg)));
+                        }
+                    }
+                }
+
+                Data::Float(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            if j == 0 {
+                                return Err(RuntimeError::new(ErrorKind::DivideByZero, "[div] Attempted to divide by zero.".to_string()));
+                            }
+                            else {
+                                self.zero_flag = i / j as f64 == 0.0;
+                                self.registers.insert(reg.to_string(), Data::Float(i / j as f64));
+                            }
+                        }
+                        Data::Float(j) => {
+                            if j == 0.0 {
+                                return Err(RuntimeError::new(ErrorKind::DivideByZero, "[div] Attempted to divide by zero.".to_string()));
+                            }
+                            else {
+                                self.zero_flag = i / j == 0.0;
+                                self.registers.insert(reg.to_string(), Data::Float(i / j));
+                            }
+                        }
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[div] Attempted to divide non-numeric data from register: {}", reg)));
+                        }
+                    }
+                }
+
+                _ => {
+                    return Err(RuntimeError::new(ErrorKind::Type, format!("[div] Attempted to divide from non-numeric register: {}", reg)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn mul(&mut self, reg: &String, data: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[mul] Attempted to multiply non-existant register: {}", reg)));
+        }
+
+        if self.is_register(data) {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.registers.get(data).unwrap();
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            let result = i.checked_mul(*j).ok_or_else(|| overflow("mul"))?;
+                            self.zero_flag = result == 0;
+                            self.registers.insert(reg.to_string(), Data::Int(result));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = *i as f64 * j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 * j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[mul] Attempted to multiply non-numeric data from register: {}", reg)));
+                        }
+                    };
+                }
+
+                Data::Float(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.zero_flag = i * *j as f64 == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i * *j as f64));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = i * j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i * j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[mul] Attempted to multiply non-numeric data from register: {}", reg)));
+                        }
+                    };
+                }
+
+                _ => {
+                    return Err(RuntimeError::new(ErrorKind::Type, format!("[mul] Attempted to multiply from non-numeric register: {}", reg)));
+                }
+            }
+        }
+
+        else {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.determine_type(data)?;
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            let result = i.checked_mul(j).ok_or_else(|| overflow("mul"))?;
+                            self.zero_flag = result == 0;
+                            self.registers.insert(reg.to_string(), Data::Int(result));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = *i as f64 * j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(*i as f64 * j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[mul] Attempted to multiply non-numeric data from register: {}", reg)));
+                        }
+                    };
+                }
+
+                Data::Float(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.zero_flag = i * j as f64 == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i * j as f64));
+                        },
+                        Data::Float(j) => {
+                            self.zero_flag = i * j == 0.0;
+                            self.registers.insert(reg.to_string(), Data::Float(i * j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[mul] Attempted to multiply non-numeric data from register: {}", reg)));
+                        }
+                    };
+                }
+
+                _ => {
+                    return Err(RuntimeError::new(ErrorKind::Type, format!("[mul] Attempted to multiply from non-numeric register: {}", reg)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn xor(&mut self, reg: &String, data: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[xor] Attempted to xor non-existant register: {}", reg)));
+        }
+
+        if self.is_register(data) {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.registers.get(data).unwrap();
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.zero_flag = *i ^ j == 0;
+                            self.registers.insert(reg.to_string(), Data::Int(i ^ j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[xor] Attempted to xor non-integer data from register: {}", reg)));
+                        }
+                    };
+                }
+
+                _ => {
+                    return Err(RuntimeError::new(ErrorKind::Type, format!("[xor] Attempted to xor from non-numeric register: {}", reg)));
+                }
+            }
+        }
+
+        else {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.determine_type(data)?;
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.zero_flag = *i ^ j == 0;
+                            self.registers.insert(reg.to_string(), Data::Int(i ^ j));
+                        },
+                        _ => {
+                            return Err(RuntimeError::new(ErrorKind::Type, format!("[xor] Attempted to xor non-integer data from register: {}", reg)));
+                        }
+                    };
+                }
+
+                _ => {
+                    return Err(RuntimeError::new(ErrorKind::Type, format!("[xor] Attempted to xor from non-numeric register: {}", reg)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn cmp(&mut self, reg: &String, data: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[cmp] Attempted to compare non-existant register: {}", reg)));
+        }
+
+        if self.is_register(data) {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.registers.get(data).unwrap();
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.equal_flag = i == j;
+                            self.greater_flag = i > j;
+                            self.lesser_flag = i < j;
+                        }
+
+                        Data::Float(j) => {
+                            self.equal_flag = *i as f64 == *j;
+                            self.greater_flag = *i as f64 > *j;
+                            self.lesser_flag = (*i as f64) < *j;
+                        }
+
+                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+                    }
+                }
+
+                Data::Bool(i) => {
+                    match data_data {
+                        Data::Bool(j) => {
+                            self.equal_flag = i == j;
+                            self.greater_flag = false;
+                            self.lesser_flag = false;
+                        }
+
+                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+                    }
+                }
+
+                Data::Float(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.equal_flag = *i == *j as f64;
+                            self.greater_flag = *i > *j as f64;
+                            self.lesser_flag = *i < *j as f64;
+                        }
+
+                        Data::Float(j) => {
+                            self.equal_flag = i == j;
+                            self.greater_flag = i > j;
+                            self.lesser_flag = i < j;
+                        }
+
+                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+                    }
+                }
+
+                Data::String(i) => {
+                    match data_data {
+                        Data::String(j) => {
+                            self.equal_flag = i == j;
+                            self.greater_flag = false;
+                            self.lesser_flag = false;
+                        }
+
+                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+                    }
+                }
+                Data::Function(i) => {
+                    match data_data {
+                        Data::Function(j) => {
+                            self.equal_flag = i == j;
+                            self.greater_flag = false;
+                            self.lesser_flag = false;
+                        }
+
+                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+                    }
+                }
+
+                _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+            }
+        }
+        else {
+            let reg_data = self.registers.get(reg).unwrap();
+            let data_data = self.determine_type(data)?;
+            match reg_data {
+                Data::Int(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.equal_flag = *i == j;
+                            self.greater_flag = *i > j;
+                            self.lesser_flag = *i < j;
+                        }
+
+                        Data::Float(j) => {
+                            self.equal_flag = *i as f64 == j;
+                            self.greater_flag = *i as f64 > j;
+                            self.lesser_flag = (*i as f64) < j;
+                        }
+
+                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+                    }
+                }
+
+                Data::Bool(i) => {
+                    match data_data {
+                        Data::Bool(j) => {
+                            self.equal_flag = *i == j;
+                            self.greater_flag = false;
+                            self.lesser_flag = false;
+                        }
+
+                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+                    }
+                }
+
+                Data::Float(i) => {
+                    match data_data {
+                        Data::Int(j) => {
+                            self.equal_flag = *i == j as f64;
+                            self.greater_flag = *i > j as f64;
+                            self.lesser_flag = *i < j as f64;
+                        }
+
+                        Data::Float(j) => {
+                            self.equal_flag = *i == j;
+                            self.greater_flag = *i > j;
+                            self.lesser_flag = *i < j;
+                        }
+
+                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+                    }
+                }
+
+                Data::String(i) => {
+                    match data_data {
+                        Data::String(j) => {
+                            self.equal_flag = *i == j;
+                            self.greater_flag = false;
+                            self.lesser_flag = false;
+                        }
+
+                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+                    }
+                }
+                Data::Function(i) => {
+                    match data_data {
+                        Data::Function(j) => {
+                            self.equal_flag = *i == j;
+                            self.greater_flag = false;
+                            self.lesser_flag = false;
+                        }
+
+                        _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+                    }
+                }
+
+                _ => {self.equal_flag = false; self.greater_flag = false; self.lesser_flag = false;}
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn jmp(&mut self, label: &String) -> ExecResult {
+        if self.is_register(label) { //indirect call through a function reference
+            match self.registers.get(label).unwrap() {
+                Data::Function(name) => {
+                    let name = name.clone();
+                    return self.jmp(&name);
+                }
+                _ => {
+                    return Err(RuntimeError::new(ErrorKind::Type, format!("[jmp] Attempted to jump through register not holding a function reference: {}", label)));
+                }
+            }
+        }
+
+        if let Some(native) = self.natives.get(label).cloned() {
+            let started = self.native_calls.is_some().then(Instant::now);
+            let result = (native.0)(self);
+            if let (Some(calls), Some(started)) = (&mut self.native_calls, started) {
+                calls.push((label.clone(), started.elapsed()));
+            }
+            result?;
+            return self.check_register_sizes();
+        }
+
+        if !self.functions.contains_key(label) {
+            return Err(RuntimeError::new(ErrorKind::Function, format!("[jmp] Attempted to jump to non-existant function: {}", label)));
+        }
+
+        //parameters of a function called with JMP are taken from P0, P1...
+        let param_count = self.functions.get(label).unwrap().params.len();
+        let mut args = Vec::new();
+        for i in 0..param_count {
+            match self.registers.get(&format!("P{}", i)) {
+                Some(data) => args.push(data.clone()),
+                None => {
+                    return Err(RuntimeError::new(ErrorKind::Argument, format!("[jmp] Not enough P registers to pass {} arguments to function: {}", param_count, label)));
+                }
+            }
+        }
+        self.call_function(label, args)
+    }
+
+    fn call_function(&mut self, label: &String, args: Vec<Data>) -> ExecResult {
+        let function = self.functions.get(label).unwrap().clone();
+        if args.len() != function.params.len() {
+            return Err(RuntimeError::new(ErrorKind::Argument, format!("[call] Function {} expects {} arguments, got {}", label, function.params.len(), args.len())));
+        }
+        self.check_call_depth()?;
+        self.count_instruction()?;
+
+        let caller_fp = self.registers.insert("FP".to_string(), Data::Int(self.stack.len() as i64)).unwrap();
+        let mut saved = vec![("FP".to_string(), Some(caller_fp))];
+        //the names of the caller are hidden while the call runs, it only sees its own
+        if let Some(caller) = self.frames.last() {
+            for name in caller.code.params.iter().chain(&caller.code.returns) {
+                saved.push((name.to_string(), self.registers.remove(name)));
+            }
+        }
+        self.frames.push(Frame { function: label.to_string(), code: Rc::clone(&function), pc: 0, handlers: Vec::new(), saved });
+        self.tasks.push(Task::Call);
+        self.calls_made += 1;
+        for (param, arg) in function.params.iter().zip(args) {
+            self.bind_local(param, arg);
+        }
+        for ret in &function.returns {
+            self.bind_local(ret, Data::Null);
+        }
+
+        Ok(())
+    }
+
+    //calls a TRY handler with the error kind, message and thrown value in RET0, RET1 and RET2
+    pub(crate) fn catch(&mut self, handler: &String, error: RuntimeError) -> ExecResult {
+        self.registers.insert("RET0".to_string(), Data::String(error.kind.name().to_string()));
+        self.registers.insert("RET1".to_string(), Data::String(error.message));
+        self.registers.insert("RET2".to_string(), error.value);
+        self.jmp(handler)
+    }
+
+    fn try_(&mut self, handler: &String) -> ExecResult {
+        let is_function = match self.registers.get(handler) {
+            Some(data) => matches!(data, Data::Function(_)),
+            None => self.natives.contains_key(handler) || self.functions.contains_key(handler),
+        };
+        if !is_function {
+            return Err(RuntimeError::new(ErrorKind::Function, format!("[try] Attempted to use non-existant function as handler: {}", handler)));
+        }
+
+        match self.frames.last_mut() {
+            Some(frame) => {
+                frame.handlers.push(handler.to_string());
+                Ok(())
+            }
+            None => Err(RuntimeError::new(ErrorKind::Instruction, "[try] Attempted to use TRY outside of a function".to_string())),
+        }
+    }
+
+    fn end_try(&mut self) -> ExecResult {
+        match self.frames.last_mut().and_then(|frame| frame.handlers.pop()) {
+            Some(_) => Ok(()),
+            None => Err(RuntimeError::new(ErrorKind::Instruction, "[endtry] Attempted to use ENDTRY without a matching TRY".to_string())),
+        }
+    }
+
+    fn throw(&mut self, data: &String) -> ExecResult {
+        let value = self.value_of(data)?;
+        let mut error = RuntimeError::new(ErrorKind::Thrown, value.to_string());
+        error.value = value;
+        Err(error)
+    }
+
+    //makes a call-local name usable like a register until the current function returns
+    fn bind_local(&mut self, name: &String, value: Data) {
+        let previous = self.registers.insert(name.to_string(), value);
+        self.frames.last_mut().unwrap().saved.push((name.to_string(), previous));
+    }
+
+    fn call(&mut self, label: &String, args: &[String]) -> ExecResult {
+        let values = args.iter().map(|arg| self.value_of(arg)).collect::<Result<Vec<Data>, RuntimeError>>()?;
+
+        let label = match self.registers.get(label) {
+            Some(Data::Function(name)) => name.clone(),
+            Some(_) => {
+                return Err(RuntimeError::new(ErrorKind::Type, format!("[call] Attempted to call through register not holding a function reference: {}", label)));
+            }
+            None => label.to_string(),
+        };
+
+        if self.natives.contains_key(&label) { //native functions take their arguments in P0, P1...
+            for (i, value) in values.into_iter().enumerate() {
+                if !self.is_register(&format!("P{}", i)) {
+                    return Err(RuntimeError::new(ErrorKind::Argument, format!("[call] Not enough P registers to pass {} arguments to function: {}", args.len(), label)));
+                }
+                self.registers.insert(format!("P{}", i), value);
+            }
+            return self.jmp(&label);
+        }
+
+        if !self.functions.contains_key(&label) {
+            return Err(RuntimeError::new(ErrorKind::Function, format!("[call] Attempted to call non-existant function: {}", label)));
+        }
+        self.call_function(&label, values)
+    }
+
+    //marks registers as callee-saved, their current values are restored when the function returns
+    fn local(&mut self, regs: &[String]) -> ExecResult {
+        if self.frames.is_empty() {
+            return Err(RuntimeError::new(ErrorKind::Instruction, "[local] Attempted to declare local registers outside of a function".to_string()));
+        }
+
+        for reg in regs {
+            if !self.is_register(reg) {
+                return Err(RuntimeError::new(ErrorKind::Register, format!("[local] Attempted to declare non-existant register as local: {}", reg)));
+            }
+            let value = self.registers.get(reg).cloned();
+            self.frames.last_mut().unwrap().saved.push((reg.to_string(), value));
+        }
+        Ok(())
+    }
+
+    fn callr(&mut self, reg: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[callr] Attempted to call through non-existant register: {}", reg)));
+        }
+        self.jmp(reg)
+    }
+
+    fn je(&mut self, label: &String) -> ExecResult {
+        if self.equal_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn jne(&mut self, label: &String) -> ExecResult {
+        if !self.equal_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn jg(&mut self, label: &String) -> ExecResult {
+        if self.greater_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn jge(&mut self, label: &String) -> ExecResult {
+        if self.greater_flag || self.equal_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn jl(&mut self, label: &String) -> ExecResult {
+        if self.greater_flag || self.equal_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn jle(&mut self, label: &String) -> ExecResult {
+        if self.greater_flag || self.equal_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn jz(&mut self, label: &String) -> ExecResult {
+        if self.zero_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn jnz(&mut self, label: &String) -> ExecResult {
+        if !self.zero_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn jeof(&mut self, label: &String) -> ExecResult {
+        if self.eof_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn jneof(&mut self, label: &String) -> ExecResult {
+        if !self.eof_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn jerr(&mut self, label: &String) -> ExecResult {
+        if self.error_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn jnerr(&mut self, label: &String) -> ExecResult {
+        if !self.error_flag {
+            self.jmp(label)?;
+        }
+        Ok(())
+    }
+
+    fn setg(&mut self, reg: &String) -> ExecResult {
+        if self.greater_flag {
+            self.registers.insert(reg.to_string(), Data::Bool(true));
+        }
+        else {
+            self.registers.insert(reg.to_string(), Data::Bool(false));
+        }
+        Ok(())
+    }
+
+    fn setge(&mut self, reg: &String) -> ExecResult {
+        if self.greater_flag || self.equal_flag {
+            self.registers.insert(reg.to_string(), Data::Bool(true));
+        }
+        else {
+            self.registers.insert(reg.to_string(), Data::Bool(false));
+        }
+        Ok(())
+    }
+
+    fn setl(&mut self, reg: &String) -> ExecResult {
+        if self.lesser_flag {
+            self.registers.insert(reg.to_string(), Data::Bool(true));
+        }
+        else {
+            self.registers.insert(reg.to_string(), Data::Bool(false));
+        }
+        Ok(())
+    }
+
+    fn setle(&mut self, reg: &String) -> ExecResult {
+        if self.lesser_flag || self.equal_flag {
+            self.registers.insert(reg.to_string(), Data::Bool(true));
+        }
+        else {
+            self.registers.insert(reg.to_string(), Data::Bool(false));
+        }
+        Ok(())
+    }
+
+    fn loop_(&mut self, label: &String) -> ExecResult {
+        let counter = match self.registers.get("L0").unwrap() {
+            Data::Int(i) => *i,
+            _ => {
+                return Err(RuntimeError::new(ErrorKind::Type, "[loop] Attempted to loop with non-integer value".to_string()));
+            }
+        };
+
+        self.loop_depth += 1;
+        self.loop_counters.push(counter);
+        self.tasks.push(Task::Loop(LoopState { kind: LoopKind::Loop, label: label.to_string(), counter, iterating: false }));
+        Ok(())
+    }
+
+    fn loop_no_dec(&mut self, label: &String) -> ExecResult {
+        let count = match self.registers.get("L0").unwrap() {
+            Data::Int(i) => *i,
+            _ => {
+                return Err(RuntimeError::new(ErrorKind::Type, "[loop] Attempted to loop with non-integer value".to_string()));
+            }
+        };
+
+        self.loop_depth += 1;
+        self.loop_counters.push(count);
+        self.tasks.push(Task::Loop(LoopState { kind: LoopKind::NoDec { count }, label: label.to_string(), counter: 0, iterating: false }));
+        Ok(())
+    }
+
+    fn for_(&mut self, reg: &String, start: &String, end: &String, step: &String, label: &String) -> ExecResult {
+        if !self.is_register(reg) {
+            return Err(RuntimeError::new(ErrorKind::Register, format!("[for] Attempted to use non-existant register as loop index: {}", reg)));
+        }
+
+        let (index, end, step) = match (self.value_of(start)?, self.value_of(end)?, self.value_of(step)?) {
+            (Data::Int(start), Data::Int(end), Data::Int(step)) => (start, end, step),
+            _ => {
+                return Err(RuntimeError::new(ErrorKind::Type, "[for] Attempted to loop with non-integer start, end or step".to_string()));
+            }
+        };
+        if step == 0 {
+            return Err(RuntimeError::new(ErrorKind::Argument, "[for] Attempted to loop with a step of 0".to_string()));
+        }
+
+        self.loop_depth += 1;
+        let kind = LoopKind::For { reg: reg.to_string(), end, step };
+        self.tasks.push(Task::Loop(LoopState { kind, label: label.to_string(), counter: index, iterating: false }));
+        Ok(())
+    }
+
+    fn break_(&mut self) -> ExecResult {
+        if self.loop_depth == 0 {
+            return Err(RuntimeError::new(ErrorKind::Instruction, "[break] Attempted to break outside of a loop".to_string()));
+        }
+        self.signal = Some(Signal::Break);
+        Ok(())
+    }
+
+    fn continue_(&mut self) -> ExecResult {
+        if self.loop_depth == 0 {
+            return Err(RuntimeError::new(ErrorKind::Instruction, "[continue] Attempted to continue outside of a loop".to_string()));
+        }
+        self.signal = Some(Signal::Continue);
+        Ok(())
+    }
+
+    //runs the exit handlers and returns the exit code, either from the exit builtin or what the entry function put in RET0
+    pub fn finish(&mut self) -> Result<i32, RuntimeError> {
+        self.phase = Phase::Running;
+        self.run_to_end()
+    }
+
+    //writes an uncaught error and its backtrace to the error stream
+    pub fn report_error(&mut self, error: &RuntimeError) {
+        error.report(&mut self.streams.errors);
+    }
+
+    //runs the entry function with the given program arguments, returns the exit code
+    pub fn run(&mut self, args: &[String]) -> Result<i32, RuntimeError> {
+        self.start(args)?;
+        self.run_to_end()
+    }
+}
+
+//integer results that do not fit in 64 bits are errors instead of wrapping around
+pub(crate) fn overflow(instruction: &str) -> RuntimeError {
+    RuntimeError::new(ErrorKind::Overflow, format!("[{}] The result does not fit in an integer", instruction))
+}
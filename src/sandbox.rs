@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::{ErrorKind, RuntimeError};
+
+//directories scripts may read from / write to and whether they may run programs, nothing is allowed by default
+#[derive(Debug, Default)]
+pub(crate) struct Sandbox {
+    pub(crate) read: Vec<PathBuf>,
+    pub(crate) write: Vec<PathBuf>,
+    pub(crate) exec: bool,
+}
+
+impl Sandbox {
+    pub(crate) fn allow(dirs: &mut Vec<PathBuf>, dir: &str) -> Result<(), String> {
+        match Path::new(dir).canonicalize() {
+            Ok(dir) => {
+                dirs.push(dir);
+                Ok(())
+            }
+            Err(error) => Err(format!("Cannot allow access to {}: {}", dir, error)),
+        }
+    }
+
+    //absolute path with symlinks and .. resolved, the file itself does not have to exist yet.
+    //A symlink that points nowhere is rejected, writing to it would create the file it points to wherever that is
+    fn resolve(path: &str) -> Option<PathBuf> {
+        let path = Path::new(path);
+        match path.canonicalize() {
+            Ok(path) => Some(path),
+            Err(_) if path.symlink_metadata().is_ok() => None,
+            Err(_) => {
+                let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+                Some(parent.canonicalize().ok()?.join(path.file_name()?))
+            }
+        }
+    }
+
+    pub(crate) fn check(&self, path: &str, write: bool, instruction: &str) -> Result<PathBuf, RuntimeError> {
+        let dirs = if write { &self.write } else { &self.read };
+        match Sandbox::resolve(path) {
+            Some(resolved) if dirs.iter().any(|dir| resolved.starts_with(dir)) => Ok(resolved),
+            _ => Err(RuntimeError::new(ErrorKind::Permission, format!(
+                "[{}] {} access to {} is not allowed, use --allow-{}=dir", instruction, if write { "Write" } else { "Read" }, path, if write { "write" } else { "read" }
+            ))),
+        }
+    }
+}
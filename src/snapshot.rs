@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::error::{ErrorKind, LoadError, RuntimeError};
+use crate::json::Json;
+use crate::runtime::{Frame, Function, RunTime, Signal};
+use crate::step::{LoopKind, LoopState, Phase, Task};
+
+//changed whenever the layout changes, snapshots of another version are rejected
+const SNAPSHOT_VERSION: i64 = 1;
+
+fn invalid(what: &str) -> String {
+    format!("missing or invalid {}", what)
+}
+
+fn get<'a>(json: &'a Json, key: &str) -> Result<&'a Json, String> {
+    json.get(key).ok_or_else(|| invalid(key))
+}
+
+fn get_i64(json: &Json, key: &str) -> Result<i64, String> {
+    get(json, key)?.as_i64().ok_or_else(|| invalid(key))
+}
+
+fn get_usize(json: &Json, key: &str) -> Result<usize, String> {
+    usize::try_from(get_i64(json, key)?).map_err(|_| invalid(key))
+}
+
+fn get_bool(json: &Json, key: &str) -> Result<bool, String> {
+    get(json, key)?.as_bool().ok_or_else(|| invalid(key))
+}
+
+fn get_string(json: &Json, key: &str) -> Result<String, String> {
+    get(json, key)?.as_str().map(str::to_string).ok_or_else(|| invalid(key))
+}
+
+fn get_array<'a>(json: &'a Json, key: &str) -> Result<&'a [Json], String> {
+    get(json, key)?.as_array().ok_or_else(|| invalid(key))
+}
+
+fn get_object<'a>(json: &'a Json, key: &str) -> Result<&'a [(String, Json)], String> {
+    get(json, key)?.as_object().ok_or_else(|| invalid(key))
+}
+
+fn string_array(json: &Json, what: &str) -> Result<Vec<String>, String> {
+    let items = json.as_array().ok_or_else(|| invalid(what))?;
+    items.iter().map(|item| item.as_str().map(str::to_string).ok_or_else(|| invalid(what))).collect()
+}
+
+fn get_strings(json: &Json, key: &str) -> Result<Vec<String>, String> {
+    string_array(get(json, key)?, key)
+}
+
+fn strings(items: &[String]) -> Json {
+    Json::Array(items.iter().map(|item| Json::string(item)).collect())
+}
+
+//HashMap order changes from run to run, sorting keeps snapshots of the same state identical
+fn sorted<T>(map: &HashMap<String, T>, to_json: impl Fn(&T) -> Json) -> Json {
+    let mut entries = map.iter().collect::<Vec<(&String, &T)>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    Json::Object(entries.into_iter().map(|(key, value)| (key.to_string(), to_json(value))).collect())
+}
+
+//every value is an object tagged with its type, {"int": 5}, except null
+fn data_to_json(data: &Data) -> Json {
+    match data {
+        Data::Int(i) => Json::object(vec![("int", Json::int(*i))]),
+        //NaN and the infinities are not JSON numbers, they are stored as strings
+        Data::Float(f) if f.is_finite() => Json::object(vec![("float", Json::Number(format!("{:?}", f)))]),
+        Data::Float(f) => Json::object(vec![("float", Json::String(f.to_string()))]),
+        Data::String(s) => Json::object(vec![("string", Json::string(s))]),
+        Data::Bool(b) => Json::object(vec![("bool", Json::Bool(*b))]),
+        Data::Function(name) => Json::object(vec![("function", Json::string(name))]),
+        Data::Struct(name, fields) => Json::object(vec![
+            ("struct", Json::string(name)),
+            ("fields", Json::Object(fields.iter().map(|(field, value)| (field.to_string(), data_to_json(value))).collect())),
+        ]),
+        Data::List(items) => Json::object(vec![("list", Json::Array(items.iter().map(data_to_json).collect()))]),
+        Data::File(handle) => Json::object(vec![("file", Json::int(*handle as i64))]),
+        Data::Null => Json::Null,
+    }
+}
+
+fn data_from_json(json: &Json) -> Result<Data, String> {
+    let (tag, value) = match json {
+        Json::Null => return Ok(Data::Null),
+        Json::Object(fields) if !fields.is_empty() => (fields[0].0.as_str(), &fields[0].1),
+        _ => return Err(invalid("value")),
+    };
+    let data = match tag {
+        "int" => value.as_i64().map(Data::Int),
+        "float" => match value {
+            Json::Number(number) | Json::String(number) => number.parse().ok().map(Data::Float),
+            _ => None,
+        },
+        "string" => value.as_str().map(|s| Data::String(s.to_string())),
+        "bool" => value.as_bool().map(Data::Bool),
+        "function" => value.as_str().map(|name| Data::Function(name.to_string())),
+        "struct" => {
+            let fields = get_object(json, "fields")?.iter()
+                .map(|(field, value)| Ok((field.to_string(), data_from_json(value)?)))
+                .collect::<Result<Vec<(String, Data)>, String>>()?;
+            value.as_str().map(|name| Data::Struct(name.to_string(), fields))
+        }
+        "list" => match value.as_array() {
+            Some(items) => Some(Data::List(items.iter().map(data_from_json).collect::<Result<Vec<Data>, String>>()?)),
+            None => None,
+        },
+        "file" => value.as_i64().and_then(|handle| usize::try_from(handle).ok()).map(Data::File),
+        _ => None,
+    };
+    data.ok_or_else(|| invalid(&format!("{} value", tag)))
+}
+
+fn data_map_from_json(json: &Json, key: &str) -> Result<HashMap<String, Data>, String> {
+    get_object(json, key)?.iter().map(|(name, value)| Ok((name.to_string(), data_from_json(value)?))).collect()
+}
+
+fn error_to_json(error: &RuntimeError) -> Json {
+    Json::object(vec![
+        ("kind", Json::string(error.kind.name())),
+        ("message", Json::string(&error.message)),
+        ("value", data_to_json(&error.value)),
+        ("backtrace", strings(&error.backtrace)),
+    ])
+}
+
+fn error_from_json(json: &Json) -> Result<RuntimeError, String> {
+    let kind = ErrorKind::from_name(&get_string(json, "kind")?).ok_or_else(|| invalid("kind"))?;
+    let mut error = RuntimeError::new(kind, get_string(json, "message")?);
+    error.value = data_from_json(get(json, "value")?)?;
+    error.backtrace = get_strings(json, "backtrace")?;
+    Ok(error)
+}
+
+fn phase_to_json(phase: &Phase) -> Json {
+    match phase {
+        Phase::Idle => Json::string("idle"),
+        Phase::Running => Json::string("running"),
+        Phase::Exiting(code) => Json::object(vec![("exiting", Json::int(*code as i64))]),
+        Phase::Halted(code) => Json::object(vec![("halted", Json::int(*code as i64))]),
+        Phase::Failed(error) => Json::object(vec![("failed", error_to_json(error))]),
+    }
+}
+
+fn phase_from_json(json: &Json) -> Result<Phase, String> {
+    let code = |key| get_i64(json, key).ok().and_then(|code| i32::try_from(code).ok());
+    match json.as_str() {
+        Some("idle") => return Ok(Phase::Idle),
+        Some("running") => return Ok(Phase::Running),
+        _ => {}
+    }
+    if let Some(code) = code("exiting") {
+        return Ok(Phase::Exiting(code));
+    }
+    if let Some(code) = code("halted") {
+        return Ok(Phase::Halted(code));
+    }
+    match json.get("failed") {
+        Some(error) => Ok(Phase::Failed(error_from_json(error)?)),
+        None => Err(invalid("phase")),
+    }
+}
+
+fn signal_to_json(signal: &Option<Signal>) -> Json {
+    match signal {
+        None => Json::Null,
+        Some(Signal::Break) => Json::string("break"),
+        Some(Signal::Continue) => Json::string("continue"),
+        Some(Signal::Exit(code)) => Json::object(vec![("exit", Json::int(*code as i64))]),
+    }
+}
+
+fn signal_from_json(json: &Json) -> Result<Option<Signal>, String> {
+    match json {
+        Json::Null => Ok(None),
+        Json::String(signal) if signal == "break" => Ok(Some(Signal::Break)),
+        Json::String(signal) if signal == "continue" => Ok(Some(Signal::Continue)),
+        _ => match get_i64(json, "exit").ok().and_then(|code| i32::try_from(code).ok()) {
+            Some(code) => Ok(Some(Signal::Exit(code))),
+            None => Err(invalid("signal")),
+        },
+    }
+}
+
+//calls are only a marker, their state is in frames
+fn task_to_json(task: &Task) -> Json {
+    let state = match task {
+        Task::Call => return Json::string("call"),
+        Task::Loop(state) => state,
+    };
+    let mut fields = match &state.kind {
+        LoopKind::Loop => vec![("loop", Json::string("loop"))],
+        LoopKind::NoDec { count } => vec![("loop", Json::string("nodec")), ("count", Json::int(*count))],
+        LoopKind::For { reg, end, step } => {
+            vec![("loop", Json::string("for")), ("reg", Json::string(reg)), ("end", Json::int(*end)), ("step", Json::int(*step))]
+        }
+    };
+    fields.push(("label", Json::string(&state.label)));
+    fields.push(("counter", Json::int(state.counter)));
+    fields.push(("iterating", Json::Bool(state.iterating)));
+    Json::object(fields)
+}
+
+fn task_from_json(json: &Json) -> Result<Task, String> {
+    if json.as_str() == Some("call") {
+        return Ok(Task::Call);
+    }
+    let kind = match get_string(json, "loop")?.as_str() {
+        "loop" => LoopKind::Loop,
+        "nodec" => LoopKind::NoDec { count: get_i64(json, "count")? },
+        "for" => LoopKind::For { reg: get_string(json, "reg")?, end: get_i64(json, "end")?, step: get_i64(json, "step")? },
+        _ => return Err(invalid("loop")),
+    };
+    Ok(Task::Loop(LoopState {
+        kind,
+        label: get_string(json, "label")?,
+        counter: get_i64(json, "counter")?,
+        iterating: get_bool(json, "iterating")?,
+    }))
+}
+
+fn frame_to_json(frame: &Frame) -> Json {
+    //a name that did not exist before the call has no value and is removed again when it returns
+    let saved = frame.saved.iter().map(|(name, value)| match value {
+        Some(value) => Json::object(vec![("name", Json::string(name)), ("value", data_to_json(value))]),
+        None => Json::object(vec![("name", Json::string(name))]),
+    });
+    Json::object(vec![
+        ("function", Json::string(&frame.function)),
+        ("pc", Json::int(frame.pc as i64)),
+        ("handlers", strings(&frame.handlers)),
+        ("saved", Json::Array(saved.collect())),
+    ])
+}
+
+fn frame_from_json(json: &Json, functions: &HashMap<String, Rc<Function>>) -> Result<Frame, String> {
+    let function = get_string(json, "function")?;
+    let code = functions.get(&function).ok_or_else(|| format!("frame of unknown function {}", function))?;
+    let pc = get_usize(json, "pc")?;
+    if pc > code.body.len() {
+        return Err(format!("frame of {} is past the end of the function", function));
+    }
+    let saved = get_array(json, "saved")?.iter()
+        .map(|saved| Ok((get_string(saved, "name")?, saved.get("value").map(data_from_json).transpose()?)))
+        .collect::<Result<Vec<(String, Option<Data>)>, String>>()?;
+    Ok(Frame { function, code: Rc::clone(code), pc, handlers: get_strings(json, "handlers")?, saved })
+}
+
+impl RunTime {
+    //the complete state of the program as JSON, restore continues from it exactly where it was.
+    //what the host configured (streams, sandbox, limits, natives) and open files are not part of it
+    pub fn snapshot(&self) -> String {
+        let functions = sorted(&self.functions, |function| Json::object(vec![
+            ("params", strings(&function.params)),
+            ("returns", strings(&function.returns)),
+            ("body", strings(&function.body)),
+            ("lines", Json::Array(function.lines.iter().map(|line| Json::int(*line as i64)).collect())),
+        ]));
+        let flags = self.flags();
+        Json::object(vec![
+            ("version", Json::int(SNAPSHOT_VERSION)),
+            ("entry", Json::string(&self.entry)),
+            ("phase", phase_to_json(&self.phase)),
+            ("exit_value", data_to_json(&self.exit_value)),
+            ("functions", functions),
+            ("structs", sorted(&self.structs, |fields| strings(fields))),
+            ("constants", sorted(&self.constants, data_to_json)),
+            ("aliases", sorted(&self.aliases, |reg| Json::string(reg))),
+            ("registers", sorted(&self.registers, data_to_json)),
+            ("stack", Json::Array(self.stack.iter().map(data_to_json).collect())),
+            ("max_stack_size", Json::int(self.max_stack_size as i64)),
+            ("flags", Json::object(vec![
+                ("equal", Json::Bool(flags.equal)),
+                ("greater", Json::Bool(flags.greater)),
+                ("lesser", Json::Bool(flags.lesser)),
+                ("zero", Json::Bool(flags.zero)),
+                ("eof", Json::Bool(flags.eof)),
+                ("error", Json::Bool(flags.error)),
+            ])),
+            ("frames", Json::Array(self.frames.iter().map(frame_to_json).collect())),
+            ("tasks", Json::Array(self.tasks.iter().map(task_to_json).collect())),
+            ("loop_depth", Json::int(self.loop_depth as i64)),
+            ("loop_counters", Json::Array(self.loop_counters.iter().map(|counter| Json::int(*counter)).collect())),
+            ("signal", signal_to_json(&self.signal)),
+            ("atexit_handlers", strings(&self.atexit_handlers)),
+            ("next_file_handle", Json::int(self.next_file_handle as i64)),
+            ("waiting_for_input", Json::Bool(self.waiting_for_input)),
+        ]).to_pretty_string()
+    }
+
+    //replaces the program and its state with a snapshot, keeping what the host configured.
+    //files the program had open are closed. Nothing changes if the snapshot is invalid
+    pub fn restore(&mut self, snapshot: &str) -> Result<(), LoadError> {
+        self.restore_json(snapshot).map_err(|message| LoadError::new(format!("Invalid snapshot: {}", message)))
+    }
+
+    //a runtime using stdin, stdout and stderr, restored from the snapshot in the file at path
+    pub fn from_snapshot<P: AsRef<Path>>(path: P) -> Result<RunTime, LoadError> {
+        let path = path.as_ref();
+        let snapshot = read_to_string(path).map_err(|error| LoadError::new(format!("Cannot read {}: {}", path.display(), error)))?;
+        let mut runtime = RunTime::default();
+        runtime.restore(&snapshot)?;
+        Ok(runtime)
+    }
+
+    fn restore_json(&mut self, snapshot: &str) -> Result<(), String> {
+        let json = Json::parse(snapshot)?;
+        let version = get_i64(&json, "version")?;
+        if version != SNAPSHOT_VERSION {
+            return Err(format!("version {} is not supported, expected {}", version, SNAPSHOT_VERSION));
+        }
+
+        let mut functions = HashMap::new();
+        for (name, function) in get_object(&json, "functions")? {
+            let lines = get_array(function, "lines")?.iter()
+                .map(|line| line.as_i64().and_then(|line| usize::try_from(line).ok()).ok_or_else(|| invalid("lines")))
+                .collect::<Result<Vec<usize>, String>>()?;
+            let function = Function {
+                params: get_strings(function, "params")?,
+                returns: get_strings(function, "returns")?,
+                body: get_strings(function, "body")?,
+                lines,
+            };
+            if function.lines.len() != function.body.len() {
+                return Err(format!("lines of function {} do not match its body", name));
+            }
+            functions.insert(name.to_string(), Rc::new(function));
+        }
+        let structs = get_object(&json, "structs")?.iter()
+            .map(|(name, fields)| Ok((name.to_string(), string_array(fields, "structs")?)))
+            .collect::<Result<HashMap<String, Vec<String>>, String>>()?;
+        let aliases = get_object(&json, "aliases")?.iter()
+            .map(|(alias, reg)| Ok((alias.to_string(), reg.as_str().ok_or_else(|| invalid("aliases"))?.to_string())))
+            .collect::<Result<HashMap<String, String>, String>>()?;
+        let frames = get_array(&json, "frames")?.iter().map(|frame| frame_from_json(frame, &functions)).collect::<Result<Vec<Frame>, String>>()?;
+        let tasks = get_array(&json, "tasks")?.iter().map(task_from_json).collect::<Result<Vec<Task>, String>>()?;
+        if tasks.iter().filter(|task| matches!(task, Task::Call)).count() != frames.len() {
+            return Err("the calls in tasks do not match the frames".to_string());
+        }
+        let loop_counters = get_array(&json, "loop_counters")?.iter()
+            .map(|counter| counter.as_i64().ok_or_else(|| invalid("loop_counters")))
+            .collect::<Result<Vec<i64>, String>>()?;
+        //LOOP and LOOPNODEC keep their counter in loop_counters, every loop counts towards loop_depth
+        let loops = tasks.iter().filter(|task| matches!(task, Task::Loop(_))).count();
+        let counted = tasks.iter().filter(|task| matches!(task, Task::Loop(LoopState { kind: LoopKind::Loop | LoopKind::NoDec { .. }, .. }))).count();
+        if get_usize(&json, "loop_depth")? != loops || loop_counters.len() != counted {
+            return Err("the loops in tasks do not match loop_depth and loop_counters".to_string());
+        }
+        let flags = get(&json, "flags")?;
+
+        //everything is read before anything is replaced, so a broken snapshot leaves the runtime as it was
+        let state = (
+            get_string(&json, "entry")?,
+            phase_from_json(get(&json, "phase")?)?,
+            get(&json, "exit_value").and_then(data_from_json)?,
+            data_map_from_json(&json, "constants")?,
+            data_map_from_json(&json, "registers")?,
+            get_array(&json, "stack")?.iter().map(data_from_json).collect::<Result<Vec<Data>, String>>()?,
+            get_usize(&json, "max_stack_size")?,
+            [get_bool(flags, "equal")?, get_bool(flags, "greater")?, get_bool(flags, "lesser")?, get_bool(flags, "zero")?, get_bool(flags, "eof")?, get_bool(flags, "error")?],
+            get_usize(&json, "loop_depth")?,
+            signal_from_json(get(&json, "signal")?)?,
+            get_strings(&json, "atexit_handlers")?,
+            get_usize(&json, "next_file_handle")?,
+            get_bool(&json, "waiting_for_input")?,
+        );
+        let (entry, phase, exit_value, constants, registers, stack, max_stack_size, flags, loop_depth, signal, atexit_handlers, next_file_handle, waiting_for_input) = state;
+        //every program has these, instructions and builtins use them without checking
+        if let Some(missing) = ["P0", "RET0", "FP", "L0"].iter().find(|reg| !registers.contains_key(**reg)) {
+            return Err(format!("missing register {}", missing));
+        }
+
+        self.entry = entry;
+        self.phase = phase;
+        self.exit_value = exit_value;
+        self.functions = functions;
+        self.structs = structs;
+        self.constants = constants;
+        self.aliases = aliases;
+        self.registers = registers;
+        self.stack = stack;
+        self.max_stack_size = max_stack_size;
+        [self.equal_flag, self.greater_flag, self.lesser_flag, self.zero_flag, self.eof_flag, self.error_flag] = flags;
+        self.frames = frames;
+        self.tasks = tasks;
+        self.loop_depth = loop_depth;
+        self.loop_counters = loop_counters;
+        self.signal = signal;
+        self.atexit_handlers = atexit_handlers;
+        self.files.clear();
+        self.next_file_handle = next_file_handle;
+        self.waiting_for_input = waiting_for_input;
+        Ok(())
+    }
+}
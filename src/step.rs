@@ -0,0 +1,441 @@
+use std::rc::Rc;
+
+use crate::data::Data;
+use crate::error::{ErrorKind, ExecResult, RuntimeError};
+use crate::runtime::{overflow, written_register, Frame, RunTime, Signal};
+use crate::streams::Input;
+
+//where a program driven with step / run_for is
+#[derive(Debug, Clone)]
+pub enum Status {
+    Running, //there is more to execute
+    WaitingForInput, //an input builtin needs a line that was not fed yet, step again after feed_input or close_input
+    Halted(i32), //the program ended with this exit code, or was never started
+    Errored(RuntimeError), //an uncaught error ended the program
+}
+
+//an instruction of the program, line is its line number in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub function: String,
+    pub index: usize, //position of the instruction in the function body
+    pub line: usize,
+    pub instruction: String,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Phase {
+    Idle, //nothing started, single lines can still be run with execute
+    Running, //the entry function is executing
+    Exiting(i32), //the atexit handlers are running, with the exit code so far
+    Halted(i32),
+    Failed(RuntimeError),
+}
+
+//what step executes next, the innermost one
+#[derive(Debug)]
+pub(crate) enum Task {
+    Call, //a function call, its state is the innermost frame
+    Loop(LoopState),
+}
+
+#[derive(Debug)]
+pub(crate) enum LoopKind {
+    Loop, //LOOP, the counter counts down from L0 to 1
+    NoDec { count: i64 }, //LOOPNODEC, the counter counts the iterations up to count
+    For { reg: String, end: i64, step: i64 }, //FOR, the counter is the index
+}
+
+//a LOOP, LOOPNODEC or FOR calling its label once per iteration
+#[derive(Debug)]
+pub(crate) struct LoopState {
+    pub(crate) kind: LoopKind,
+    pub(crate) label: String,
+    pub(crate) counter: i64,
+    pub(crate) iterating: bool, //the label was called and the iteration is not finished yet
+}
+
+fn frame_location(frame: &Frame, index: usize) -> Location {
+    Location {
+        function: frame.function.to_string(),
+        index,
+        line: frame.code.lines.get(index).copied().unwrap_or(0),
+        instruction: frame.code.body.get(index).cloned().unwrap_or_default(),
+    }
+}
+
+//statuses outside 0..=255 would be truncated by the OS, so 256 would look like success
+pub(crate) fn exit_code(code: i64) -> Option<i32> {
+    (0..=255).contains(&code).then_some(code as i32)
+}
+
+pub(crate) fn no_input_error() -> RuntimeError {
+    RuntimeError::new(ErrorKind::Io, "[input] Waiting for input that was never fed".to_string())
+}
+
+impl RunTime {
+    //calls the entry function with the given program arguments, without executing any of it yet
+    pub fn start(&mut self, args: &[String]) -> ExecResult {
+        if !self.functions.contains_key(&self.entry) {
+            return Err(RuntimeError::new(ErrorKind::Function, format!("[FATAL] No {} function found.", self.entry)));
+        }
+
+        self.registers.insert("ARGC".to_string(), Data::Int(args.len() as i64));
+        self.registers.insert("ARGV".to_string(), Data::List(args.iter().map(|arg| Data::String(arg.to_string())).collect()));
+        self.start_budget();
+        self.exit_value = Data::Null;
+        self.phase = Phase::Running;
+        if let Err(error) = self.jmp(&self.entry.clone()) {
+            self.phase = Phase::Failed(error.clone());
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    //executes a single instruction, loop iteration, function return or exit handler of the started program
+    pub fn step(&mut self) -> Status {
+        if !matches!(self.phase, Phase::Running | Phase::Exiting(_)) {
+            return self.status();
+        }
+
+        let result = match self.tasks.is_empty() {
+            true => self.step_exit(),
+            false => self.step_task(0),
+        };
+        if let Err(error) = result {
+            self.phase = Phase::Failed(error);
+        }
+        self.status()
+    }
+
+    //steps until the program stops running or n steps have been executed
+    pub fn run_for(&mut self, n: u64) -> Status {
+        for _ in 0..n {
+            if !matches!(self.step(), Status::Running) {
+                break;
+            }
+        }
+        self.status()
+    }
+
+    pub fn status(&self) -> Status {
+        match &self.phase {
+            Phase::Idle => Status::Halted(0),
+            Phase::Halted(code) => Status::Halted(*code),
+            Phase::Failed(error) => Status::Errored(error.clone()),
+            Phase::Running | Phase::Exiting(_) if self.waiting_for_input => Status::WaitingForInput,
+            Phase::Running | Phase::Exiting(_) => Status::Running,
+        }
+    }
+
+    //the instruction the next step executes. None if the next step returns from a function,
+    //starts a loop iteration or exit handler instead, or nothing is running
+    pub fn location(&self) -> Option<Location> {
+        if !matches!(self.phase, Phase::Running | Phase::Exiting(_)) || self.signal.is_some() || !matches!(self.tasks.last(), Some(Task::Call)) {
+            return None;
+        }
+        let frame = self.frames.last()?;
+        (frame.pc < frame.code.body.len()).then(|| frame_location(frame, frame.pc))
+    }
+
+    //the active calls innermost first, each at the instruction that made the next call.
+    //the innermost one is at the instruction it executes next unless it is running a loop
+    pub fn backtrace(&self) -> Vec<Location> {
+        let executing = matches!(self.tasks.last(), Some(Task::Call));
+        self.frames.iter().rev().enumerate().map(|(i, frame)| {
+            let index = if i == 0 && executing { frame.pc } else { frame.pc.saturating_sub(1) };
+            frame_location(frame, index.min(frame.code.body.len().saturating_sub(1)))
+        }).collect()
+    }
+
+    pub fn call_depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    //adds to the input read by the input builtins, switching to fed input if the runtime was reading from a stream
+    pub fn feed_input(&mut self, text: &str) {
+        match &mut self.streams.input {
+            Input::Fed { pending, .. } => pending.push_str(text),
+            input => *input = Input::Fed { pending: text.to_string(), closed: false },
+        }
+    }
+
+    //ends the fed input, the input builtins read what is left and then reach the end of the input
+    pub fn close_input(&mut self) {
+        match &mut self.streams.input {
+            Input::Fed { closed, .. } => *closed = true,
+            input => *input = Input::Fed { pending: String::new(), closed: true },
+        }
+    }
+
+    //steps a started or restored program until it ends, returns the exit code. The instruction and time limits count from here
+    pub fn resume(&mut self) -> Result<i32, RuntimeError> {
+        self.start_budget();
+        self.run_to_end()
+    }
+
+    //input the program waits for can never arrive here, so waiting is an error
+    pub(crate) fn run_to_end(&mut self) -> Result<i32, RuntimeError> {
+        loop {
+            match self.step() {
+                Status::Running => {}
+                Status::WaitingForInput => return Err(no_input_error()),
+                Status::Halted(code) => return Ok(code),
+                Status::Errored(error) => return Err(error),
+            }
+        }
+    }
+
+    //steps until everything above base has finished, used by execute
+    pub(crate) fn run_tasks(&mut self, base: usize) -> ExecResult {
+        loop {
+            if self.waiting_for_input {
+                return Err(no_input_error());
+            }
+            if self.tasks.len() <= base {
+                return Ok(());
+            }
+            self.step_task(base)?;
+        }
+    }
+
+    //the entry function has returned, runs the next atexit handler or ends the program
+    fn step_exit(&mut self) -> ExecResult {
+        let code = match (self.signal.take(), &self.phase, &self.exit_value) {
+            (Some(Signal::Exit(code)), _, _) => code,
+            (_, Phase::Exiting(code), _) => *code,
+            (_, _, Data::Int(code)) => exit_code(*code).ok_or_else(|| {
+                RuntimeError::new(ErrorKind::Argument, format!("[exit] The entry function returned an exit code outside 0..=255: {}", code))
+            })?,
+            _ => 0,
+        };
+        self.phase = Phase::Exiting(code);
+
+        match self.atexit_handlers.pop() {
+            Some(handler) => {
+                self.jmp(&handler)?;
+                if self.waiting_for_input {
+                    self.atexit_handlers.push(handler);
+                }
+            }
+            None => {
+                self.streams.output.flush().map_err(|error| RunTime::io_error("exit", "output", error))?;
+                self.phase = Phase::Halted(code);
+            }
+        }
+        Ok(())
+    }
+
+    //errors unwind no further than base, what is left of them is returned
+    pub(crate) fn step_task(&mut self, base: usize) -> ExecResult {
+        let result = match self.tasks.last() {
+            Some(Task::Call) => self.step_call(),
+            Some(Task::Loop(_)) => self.step_loop(),
+            None => Ok(()),
+        };
+        match result {
+            Ok(()) => Ok(()),
+            Err(error) => self.unwind(error, base),
+        }
+    }
+
+    fn step_call(&mut self) -> ExecResult {
+        let signalled = self.signal.is_some(); //BREAK / CONTINUE / exit skips the rest of the function
+        let frame = self.frames.last_mut().unwrap();
+        if signalled || frame.pc >= frame.code.body.len() {
+            return self.return_from_call();
+        }
+
+        let code = Rc::clone(&frame.code);
+        let pc = frame.pc;
+        frame.pc += 1;
+        //only RET0 written by the entry function is its exit code, not what a builtin or call left there
+        let sets_exit_value = self.frames.len() == 1 && matches!(self.phase, Phase::Running) && written_register(&code.body[pc]).as_deref() == Some("RET0");
+        self.execute_line(&code.body[pc])?;
+        if sets_exit_value {
+            self.exit_value = self.registers.get("RET0").unwrap().clone();
+        }
+        if self.waiting_for_input { //executed again once there is input
+            self.frames.last_mut().unwrap().pc = pc;
+        }
+        Ok(())
+    }
+
+    fn return_from_call(&mut self) -> ExecResult {
+        let frame = self.frames.last().unwrap();
+        let (function, code) = (frame.function.clone(), Rc::clone(&frame.code));
+        let mut result = Ok(());
+        for (i, ret) in code.returns.iter().enumerate() {
+            let value = self.registers.get(ret).unwrap().clone();
+            if !self.is_register(&format!("RET{}", i)) {
+                result = Err(RuntimeError::new(ErrorKind::Argument, format!("[call] Not enough RET registers to return {} values from function: {}", code.returns.len(), function)));
+                break;
+            }
+            self.registers.insert(format!("RET{}", i), value);
+        }
+        if result.is_ok() && self.frames.len() == 1 && matches!(self.phase, Phase::Running) && !code.returns.is_empty() { //fun main -> code
+            self.exit_value = self.registers.get("RET0").unwrap().clone();
+        }
+        self.pop_frame();
+        result?;
+
+        if self.limits.value_size.is_some() {
+            for i in 0..code.returns.len() {
+                self.check_value_size(&format!("RET{}", i))?;
+            }
+        }
+        Ok(())
+    }
+
+    //ends the innermost call, restoring in reverse so a register saved twice ends up with its oldest value
+    fn pop_frame(&mut self) {
+        self.tasks.pop();
+        let frame = self.frames.pop().unwrap();
+        for (name, value) in frame.saved.into_iter().rev() {
+            match value {
+                Some(value) => self.registers.insert(name, value),
+                None => self.registers.remove(&name),
+            };
+        }
+    }
+
+    fn step_loop(&mut self) -> ExecResult {
+        let Some(Task::Loop(mut state)) = self.tasks.pop() else {
+            return Ok(());
+        };
+
+        if state.iterating {
+            match self.signal {
+                Some(Signal::Break) => {
+                    self.signal = None;
+                    self.end_loop(&state);
+                    return Ok(());
+                }
+                Some(Signal::Exit(_)) => { //left set so it keeps unwinding
+                    self.end_loop(&state);
+                    return Ok(());
+                }
+                Some(Signal::Continue) => self.signal = None,
+                None => {}
+            }
+            match &state.kind {
+                LoopKind::Loop => {
+                    //the body can change L0, the loop goes on from whatever it holds now
+                    let counter = match self.registers.get("L0").unwrap() {
+                        Data::Int(i) => i.checked_sub(1).ok_or_else(|| overflow("loop")),
+                        _ => Err(RuntimeError::new(ErrorKind::Type, "[loop] Attempted to loop with non-integer value".to_string())),
+                    };
+                    state.counter = match counter {
+                        Ok(counter) => counter,
+                        Err(error) => {
+                            self.end_loop(&state);
+                            return Err(error);
+                        }
+                    };
+                    self.zero_flag = state.counter == 0;
+                    *self.loop_counters.last_mut().unwrap() = state.counter;
+                }
+                LoopKind::NoDec { .. } => state.counter += 1,
+                LoopKind::For { step, .. } => match state.counter.checked_add(*step) {
+                    Some(counter) => state.counter = counter,
+                    None => { //the next index would not fit in an integer, so it is past the end too
+                        self.end_loop(&state);
+                        return Ok(());
+                    }
+                },
+            }
+        }
+
+        let more = match &state.kind {
+            LoopKind::Loop => state.counter > 0,
+            LoopKind::NoDec { count } => state.counter < *count,
+            LoopKind::For { end, step, .. } => (*step > 0 && state.counter < *end) || (*step < 0 && state.counter > *end),
+        };
+        if !more {
+            self.end_loop(&state);
+            return Ok(());
+        }
+
+        match &state.kind {
+            LoopKind::Loop => self.registers.insert("L0".to_string(), Data::Int(state.counter)),
+            LoopKind::NoDec { count } => self.registers.insert("L0".to_string(), Data::Int(*count)),
+            LoopKind::For { reg, .. } => self.registers.insert(reg.to_string(), Data::Int(state.counter)),
+        };
+        let label = state.label.clone();
+        state.iterating = true;
+        self.tasks.push(Task::Loop(state));
+        self.count_instruction()?;
+        self.jmp(&label)?;
+        if self.waiting_for_input { //the iteration is started again once there is input
+            if let Some(Task::Loop(state)) = self.tasks.last_mut() {
+                state.iterating = false;
+            }
+        }
+        Ok(())
+    }
+
+    //L0 always holds the counter of the innermost LOOP / LOOPNODEC, so nested loops
+    //get their own counter and the outer one is restored once the inner loop finishes
+    fn end_loop(&mut self, state: &LoopState) {
+        self.loop_depth -= 1;
+        let restore_l0 = match state.kind {
+            LoopKind::Loop => state.counter,
+            LoopKind::NoDec { count } => count,
+            LoopKind::For { .. } => return,
+        };
+        self.loop_counters.pop();
+        match self.loop_counters.last() {
+            Some(outer) => self.registers.insert("L0".to_string(), Data::Int(*outer)),
+            None => self.registers.insert("L0".to_string(), Data::Int(restore_l0)),
+        };
+    }
+
+    //ends calls and loops until a TRY handler of a call takes the error, adding each call to the backtrace
+    fn unwind(&mut self, mut error: RuntimeError, base: usize) -> ExecResult {
+        while self.tasks.len() > base {
+            if let Some(Task::Loop(_)) = self.tasks.last() {
+                if let Some(Task::Loop(state)) = self.tasks.pop() {
+                    self.end_loop(&state);
+                }
+                continue;
+            }
+
+            let frame = self.frames.last_mut().unwrap();
+            //running out of a limit has to end the program, so it skips the TRY handlers
+            let handler = match error.kind {
+                ErrorKind::LimitExceeded => None,
+                _ => frame.handlers.pop(),
+            };
+            match handler {
+                Some(handler) => {
+                    //the rest of the TRY region is skipped, execution continues after its ENDTRY
+                    let body = &frame.code.body;
+                    let mut depth = 0;
+                    while frame.pc < body.len() {
+                        let instruction = body[frame.pc].split_whitespace().next().unwrap_or_default().to_uppercase();
+                        frame.pc += 1;
+                        if instruction == "TRY" {
+                            depth += 1;
+                        }
+                        else if instruction == "ENDTRY" {
+                            if depth == 0 {
+                                break;
+                            }
+                            depth -= 1;
+                        }
+                    }
+                    match self.catch(&handler, error) {
+                        Ok(()) => return Ok(()),
+                        Err(next) => error = next,
+                    }
+                }
+                None => {
+                    let line = frame.code.body.get(frame.pc.saturating_sub(1)).map_or("", |line| line.as_str());
+                    error.backtrace.push(format!("{}: {}", frame.function, line));
+                    self.pop_frame();
+                }
+            }
+        }
+        Err(error)
+    }
+}
@@ -0,0 +1,128 @@
+use std::cell::RefCell;
+use std::io::{self, BufRead, Read, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Instant;
+use std::rc::Rc;
+
+use crate::error::RuntimeError;
+
+//where the input builtins read from. The process stdin is read directly instead of through a reader of
+//our own so nothing is buffered away from the menu and REPL, which read it too
+pub(crate) enum Input {
+    Stdin(Option<Receiver<io::Result<String>>>), //a read that ran out of time, its line is returned by the next read
+    Reader(Box<dyn BufRead>),
+    Fed { pending: String, closed: bool }, //given by the host with RunTime::feed_input, never blocks
+}
+
+//how a read of the input ended
+pub(crate) enum Received {
+    Done(String),
+    TooLarge, //longer than the size limit, what was read of it is gone
+    OutOfTime,
+}
+
+impl Input {
+    //reads a line, or everything for whole, of at most max bytes. Only the process stdin can stop at the deadline,
+    //it is then read on another thread which is left reading until the line arrives
+    pub(crate) fn read(&mut self, whole: bool, max: Option<usize>, deadline: Option<Instant>) -> io::Result<Received> {
+        //one byte more than allowed tells a value of exactly max bytes from a longer one, lines also get room for \r\n
+        let take = max.map_or(u64::MAX, |max| max as u64 + if whole { 1 } else { 2 });
+        let read = move |reader: &mut dyn BufRead| -> io::Result<String> {
+            let mut text = String::new();
+            let mut reader = reader.take(take);
+            match whole {
+                true => reader.read_to_string(&mut text)?,
+                false => reader.read_line(&mut text)?,
+            };
+            Ok(text)
+        };
+        let text = match self {
+            Input::Stdin(unfinished) if deadline.is_some() || unfinished.is_some() => {
+                let receiver = unfinished.take().unwrap_or_else(|| {
+                    let (sender, receiver) = mpsc::channel();
+                    std::thread::spawn(move || sender.send(read(&mut io::stdin().lock())));
+                    receiver
+                });
+                let received = match deadline {
+                    Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                    None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match received {
+                    Ok(text) => text?,
+                    Err(RecvTimeoutError::Timeout) => {
+                        *unfinished = Some(receiver);
+                        return Ok(Received::OutOfTime);
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return Err(io::Error::other("the thread reading stdin stopped")),
+                }
+            }
+            Input::Stdin(_) => read(&mut io::stdin().lock())?,
+            Input::Reader(reader) => read(reader)?,
+            Input::Fed { pending, .. } => {
+                let end = if whole { pending.len() } else { pending.find('\n').map_or(pending.len(), |i| i + 1) };
+                pending.drain(..end).collect()
+            }
+        };
+        let len = if whole { text.len() } else { text.trim_end_matches(['\r', '\n']).len() };
+        Ok(match max {
+            Some(max) if len > max => Received::TooLarge,
+            _ => Received::Done(text),
+        })
+    }
+
+    //whether a line (or everything, for whole) can be read without waiting for the host to feed more
+    pub(crate) fn ready(&self, whole: bool) -> bool {
+        match self {
+            Input::Fed { pending, closed } => *closed || (!whole && pending.contains('\n')),
+            _ => true,
+        }
+    }
+}
+
+//program input, program output and the stream diagnostics are written to
+pub(crate) struct Streams {
+    pub(crate) input: Input,
+    pub(crate) output: Box<dyn Write>,
+    pub(crate) errors: Box<dyn Write>,
+}
+
+impl Streams {
+    pub(crate) fn stdio() -> Streams {
+        Streams { input: Input::Stdin(None), output: Box::new(io::stdout()), errors: Box::new(io::stderr()) }
+    }
+}
+
+impl std::fmt::Debug for Streams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Streams")
+    }
+}
+
+//an output stream that can still be read after it has been given to a RunTime
+#[derive(Clone, Default)]
+pub struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl SharedBuffer {
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).to_string()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+//what a program run with RunTime::run_with_input printed and how it ended
+#[derive(Debug)]
+pub struct CapturedRun {
+    pub result: Result<i32, RuntimeError>,
+    pub output: String,
+    pub errors: String,
+}
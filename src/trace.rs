@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::data::Data;
+use crate::debugger::register_order;
+use crate::error::RuntimeError;
+use crate::runtime::{Flags, RunTime};
+use crate::step::{no_input_error, Status, Task};
+
+//registers and flags before an instruction, to find out what it changed
+struct State {
+    registers: HashMap<String, Data>,
+    flags: Flags,
+}
+
+impl State {
+    fn of(runtime: &RunTime) -> State {
+        State { registers: runtime.registers.clone(), flags: runtime.flags() }
+    }
+
+    //name: before → after for everything that differs in runtime, registers first
+    fn changes(&self, runtime: &RunTime) -> Vec<String> {
+        let mut registers = runtime.registers.iter()
+            .filter(|(name, value)| !self.registers.get(*name).is_some_and(|before| same(before, value)))
+            .collect::<Vec<_>>();
+        registers.sort_by_key(|(name, _)| register_order(name));
+        let mut changes = registers.iter().map(|(name, value)| {
+            let before = self.registers.get(*name).map_or("undefined".to_string(), |before| before.to_nested_string());
+            format!("{}: {} → {}", name, before, value.to_nested_string())
+        }).collect::<Vec<String>>();
+        //the names of parameters and return values go away when their call returns
+        let mut removed = self.registers.keys().filter(|name| !runtime.registers.contains_key(*name)).collect::<Vec<_>>();
+        removed.sort_by_key(|name| register_order(name));
+        changes.extend(removed.iter().map(|name| format!("{}: {} → undefined", name, self.registers[*name].to_nested_string())));
+
+        let (before, after) = (&self.flags, runtime.flags());
+        let flags = [
+            ("equal", before.equal, after.equal),
+            ("greater", before.greater, after.greater),
+            ("lesser", before.lesser, after.lesser),
+            ("zero", before.zero, after.zero),
+            ("eof", before.eof, after.eof),
+            ("error", before.error, after.error),
+        ];
+        changes.extend(flags.iter().filter(|(_, before, after)| before != after).map(|(name, before, after)| format!("{}: {} → {}", name, before, after)));
+        changes
+    }
+}
+
+//like ==, except a NaN is the same as itself so it does not show up as a change every time
+fn same(a: &Data, b: &Data) -> bool {
+    match (a, b) {
+        (Data::Int(a), Data::Int(b)) => a == b,
+        (Data::Float(a), Data::Float(b)) => a.to_bits() == b.to_bits(),
+        (Data::String(a), Data::String(b)) | (Data::Function(a), Data::Function(b)) => a == b,
+        (Data::Bool(a), Data::Bool(b)) => a == b,
+        (Data::Struct(a, a_fields), Data::Struct(b, b_fields)) => {
+            a == b && a_fields.len() == b_fields.len() && a_fields.iter().zip(b_fields).all(|((a, a_value), (b, b_value))| a == b && same(a_value, b_value))
+        }
+        (Data::List(a), Data::List(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b)),
+        (Data::File(a), Data::File(b)) => a == b,
+        (Data::Null, Data::Null) => true,
+        _ => false,
+    }
+}
+
+//runs a program printing every instruction it executes, indented by the call depth, with the registers and flags it changed
+pub struct Tracer {
+    output: Box<dyn Write>,
+    functions: Vec<String>, //only instructions of these functions are printed, every one while it is empty
+}
+
+impl Tracer {
+    pub fn new<W: Write + 'static>(output: W) -> Tracer {
+        Tracer { output: Box::new(output), functions: Vec::new() }
+    }
+
+    //once a function is given only the instructions of the given functions are printed
+    pub fn only(&mut self, function: &str) {
+        self.functions.push(function.to_string());
+    }
+
+    //steps a started or restored program until it ends, like resume. Returning from a call, the next iteration
+    //of a loop and the exit handlers are printed as well when they change something
+    pub fn run(&mut self, runtime: &mut RunTime) -> Result<i32, RuntimeError> {
+        runtime.start_budget();
+        loop {
+            let depth = runtime.call_depth();
+            let function = runtime.frames.last().map(|frame| frame.function.clone());
+            let (event, always) = match (runtime.location(), runtime.tasks.last()) {
+                (Some(location), _) => (format!("{} (line {}): {}", location.function, location.line, location.instruction), true),
+                (None, Some(Task::Call)) => (format!("return from {}", function.as_deref().unwrap_or_default()), false),
+                (None, Some(Task::Loop(state))) => (format!("loop {}", state.label), false),
+                (None, None) => ("exit".to_string(), false),
+            };
+            let traced = match &function {
+                Some(function) => self.functions.is_empty() || self.functions.contains(function),
+                None => self.functions.is_empty(),
+            };
+            let before = traced.then(|| State::of(runtime));
+
+            let status = runtime.step();
+            if let Some(before) = before {
+                let changes = before.changes(runtime);
+                if always || !changes.is_empty() {
+                    self.write(depth, &event, &changes);
+                }
+            }
+            if !matches!(status, Status::Running) {
+                let _ = self.output.flush();
+            }
+            match status {
+                Status::Running => {}
+                Status::WaitingForInput => return Err(no_input_error()),
+                Status::Halted(code) => return Ok(code),
+                Status::Errored(error) => return Err(error),
+            }
+        }
+    }
+
+    fn write(&mut self, depth: usize, event: &str, changes: &[String]) {
+        let mut line = format!("{}{}", "  ".repeat(depth.saturating_sub(1)), event);
+        if !changes.is_empty() {
+            line += &format!(" | {}", changes.join(", "));
+        }
+        //a trace that cannot be written should not stop the program it traces
+        let _ = writeln!(self.output, "{}", line);
+    }
+}
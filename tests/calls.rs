@@ -0,0 +1,41 @@
+use xasm::{Data, ErrorKind, RunTime};
+
+mod common;
+use common::run;
+
+#[test]
+fn functions_are_called_through_references() {
+    let code = "fun double\n  MUL P0, 2\nend\n\nfun apply_twice\n  CALLR R1\n  CALLR R1\n  JMP printline\nend\n\nfun main\n  MOV R1, &double\n  MOV P0, 3\n  JMP apply_twice\n  MOV R2, &printline\n  MOV P0, R1\n  JMP R2\n  CALL R2 \"called\"\nend\n";
+    let captured = run(code, "");
+    assert_eq!(captured.output, "12\n&double\ncalled\n");
+    assert_eq!(captured.result.unwrap(), 0);
+}
+
+#[test]
+fn references_compare_by_function() {
+    let mut runtime = RunTime::new("fun a\nend\n\nfun b\nend\n").unwrap();
+    runtime.execute("MOV R0, &a").unwrap();
+    runtime.execute("MOV R1, &a").unwrap();
+    runtime.execute("CMP R0, R1").unwrap();
+    assert!(runtime.flags().equal);
+    runtime.execute("CMP R0, &b").unwrap();
+    assert!(!runtime.flags().equal);
+    assert!(matches!(runtime.register("R0"), Some(Data::Function(name)) if name == "a"));
+}
+
+#[test]
+fn calling_through_something_else_fails() {
+    let mut runtime = RunTime::default();
+    let error = runtime.execute("MOV R0, &missing").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Function);
+    assert_eq!(error.message, "Reference to non-existant function: &missing");
+
+    runtime.execute("MOV R0, 5").unwrap();
+    let error = runtime.execute("CALLR R0").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Type);
+    assert_eq!(error.message, "[jmp] Attempted to jump through register not holding a function reference: R0");
+    let error = runtime.execute("CALL R0").unwrap_err();
+    assert_eq!(error.message, "[call] Attempted to call through register not holding a function reference: R0");
+    let error = runtime.execute("CALLR nothing").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Register);
+}
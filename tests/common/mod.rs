@@ -0,0 +1,19 @@
+//helpers shared by the integration tests, not every test file uses all of them
+#![allow(dead_code)]
+
+use std::io::Cursor;
+
+use xasm::{CapturedRun, Limits, RunTime, RuntimeError, SharedBuffer};
+
+//runs code with input as its stdin, the program has to load
+pub fn run(code: &str, input: &str) -> CapturedRun {
+    RunTime::run_with_input(code, input, &[]).expect("program should load")
+}
+
+//runs code without any input under the given limits, returns the result and what it printed
+pub fn run_limited(code: &str, limits: Limits) -> (Result<i32, RuntimeError>, String) {
+    let output = SharedBuffer::default();
+    let mut runtime = RunTime::with_streams(code, Cursor::new(""), output.clone(), SharedBuffer::default()).unwrap();
+    runtime.set_limits(limits);
+    (runtime.run(&[]), output.contents())
+}
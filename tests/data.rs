@@ -0,0 +1,39 @@
+use xasm::{Data, ErrorKind, RunTime};
+
+mod common;
+use common::run;
+
+const DATA: &str = "data\n  greeting \"Hello, data\"\n  limit 3\n  primes [2, 3, 5]\nend\n";
+
+#[test]
+fn constants_are_used_in_place_of_values() {
+    let code = format!("{}\nfun print_prime\n  GETI P0, primes, R0\n  JMP printline\nend\n\nfun main\n  MOV P0, greeting\n  JMP printline\n  FOR R0, 0, limit, 1, print_prime\n  LEN RET0, primes\n  ADD RET0, limit\nend\n", DATA);
+    let captured = run(&code, "");
+    assert_eq!(captured.output, "Hello, data\n2\n3\n5\n");
+    assert_eq!(captured.result.unwrap(), 6);
+}
+
+#[test]
+fn programs_writing_to_constants_are_rejected() {
+    for instruction in ["MOV limit, 5", "POP limit", "INC limit", "GETI limit, primes, 0", "SETF limit.x, 1", "LOCAL limit", "FOR limit, 0, 3, 1, main"] {
+        let error = RunTime::new(&format!("{}\nfun main\n  {}\nend\n", DATA, instruction)).unwrap_err();
+        assert_eq!(error.message, format!("Attempted to write to constant limit in function main: {}", instruction));
+    }
+}
+
+#[test]
+fn constants_cannot_be_written_at_runtime() {
+    let mut runtime = RunTime::new(DATA).unwrap();
+    let error = runtime.execute("MOV limit, 5").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Register);
+    runtime.execute("MOV R0, limit").unwrap();
+    assert!(matches!(runtime.register("R0"), Some(Data::Int(3))));
+}
+
+#[test]
+fn invalid_constants_are_rejected() {
+    let error = RunTime::new("data\n  x\nend\n").unwrap_err();
+    assert_eq!(error.message, "Invalid constant declaration: x");
+    let error = RunTime::new("data\n  x [1,\nend\n").unwrap_err();
+    assert_eq!(error.message, "Invalid value for constant x: Unknown data type: [1,");
+}
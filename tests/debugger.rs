@@ -0,0 +1,131 @@
+use std::io::Cursor;
+
+use xasm::{Breakpoint, Debugger, RunTime, SharedBuffer, Status, Watch};
+
+const PROGRAM: &str = "fun square n -> result
+  MOV result, n
+  MUL result, n
+end
+
+fun main
+  MOV R0, 0
+  MOV L0, 2
+  LOOP add_square
+  BREAKPOINT
+  MOV RET0, R0
+end
+
+fun add_square
+  CALL square L0
+  ADD R0, RET0
+end
+";
+
+fn debugger() -> (Debugger, SharedBuffer) {
+    let runtime = RunTime::with_streams(PROGRAM, Cursor::new(""), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+    let output = SharedBuffer::default();
+    let mut debugger = Debugger::new(runtime, output.clone());
+    debugger.start(&[]).unwrap();
+    (debugger, output)
+}
+
+fn last_line(output: &SharedBuffer) -> String {
+    output.contents().lines().last().unwrap_or_default().to_string()
+}
+
+#[test]
+fn stepping_into_over_and_out_of_calls() {
+    let (mut debugger, output) = debugger();
+    assert_eq!(last_line(&output), "Paused at main (line 7): MOV R0, 0");
+    debugger.command("next");
+    debugger.command("next");
+    debugger.command("step");
+    assert_eq!(last_line(&output), "Paused at add_square (line 15): CALL square L0");
+    debugger.command("step");
+    assert_eq!(last_line(&output), "Paused at square (line 2): MOV result, n");
+    debugger.command("backtrace");
+    assert!(output.contents().ends_with("#0 square (line 2): MOV result, n\n#1 add_square (line 15): CALL square L0\n#2 main (line 9): LOOP add_square\n"));
+    debugger.command("out");
+    assert_eq!(last_line(&output), "Paused at add_square (line 16): ADD R0, RET0");
+    debugger.command("print RET0");
+    assert_eq!(last_line(&output), "RET0 = 4");
+}
+
+#[test]
+fn breakpoints_pause_until_deleted() {
+    let (mut debugger, output) = debugger();
+    debugger.command("break square");
+    debugger.command("break 16");
+    debugger.command("continue");
+    assert_eq!(last_line(&output), "Breakpoint 1 at square (line 2): MOV result, n");
+    debugger.command("c");
+    assert_eq!(last_line(&output), "Breakpoint 2 at add_square (line 16): ADD R0, RET0");
+    debugger.command("delete 1");
+    debugger.command("delete 2");
+    debugger.command("c");
+    assert_eq!(last_line(&output), "BREAKPOINT at main (line 10): BREAKPOINT");
+    debugger.command("set R0 100");
+    debugger.command("c");
+    assert_eq!(last_line(&output), "Program exited with code 100");
+    assert!(matches!(debugger.runtime().status(), Status::Halted(100)));
+}
+
+#[test]
+fn breakpoints_can_be_added_by_the_host() {
+    let (mut debugger, output) = debugger();
+    assert_eq!(debugger.add_breakpoint(Breakpoint::Line(11)), 1);
+    debugger.command("c");
+    debugger.command("c");
+    assert_eq!(last_line(&output), "Breakpoint 1 at main (line 11): MOV RET0, R0");
+    debugger.command("print R0");
+    assert_eq!(last_line(&output), "R0 = 5");
+}
+
+#[test]
+fn watchpoints_pause_after_a_change() {
+    let (mut debugger, output) = debugger();
+    debugger.command("watch R0 if R0 > 0");
+    debugger.command("log stack");
+    debugger.command("continue");
+    assert!(output.contents().ends_with("Watchpoint 1: R0 changed from 0 to 4 by add_square (line 16): ADD R0, RET0\nPaused at add_square (line 15): CALL square L0\n"));
+    debugger.command("c");
+    assert!(output.contents().ends_with("Watchpoint 1: R0 changed from 4 to 5 by add_square (line 16): ADD R0, RET0\nBREAKPOINT at main (line 10): BREAKPOINT\n"));
+    assert!(!output.contents().contains("Watchpoint 2:"));
+}
+
+#[test]
+fn conditions_see_registers_and_hits() {
+    let (mut debugger, output) = debugger();
+    debugger.command("break square if hits == 2");
+    debugger.command("break 16 if RET0 == 4 and R0 < 1");
+    debugger.command("c");
+    assert_eq!(last_line(&output), "Breakpoint 2 at add_square (line 16): ADD R0, RET0");
+    debugger.command("c");
+    assert_eq!(last_line(&output), "Breakpoint 1 at square (line 2): MOV result, n");
+    debugger.command("print n");
+    assert_eq!(last_line(&output), "n = 1");
+    debugger.command("break 3 if");
+    assert_eq!(last_line(&output), "Invalid condition: , expected comparisons like R3 > 100 joined by and");
+}
+
+#[test]
+fn logging_points_never_pause() {
+    let runtime = RunTime::with_streams(PROGRAM, Cursor::new(""), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+    let output = SharedBuffer::default();
+    let mut debugger = Debugger::new(runtime, output.clone());
+    debugger.set_logging(true);
+    assert_eq!(debugger.add_point("break square").unwrap(), 1);
+    assert_eq!(debugger.add_watchpoint(Watch::Register("R0".to_string())), 2);
+    debugger.start(&[]).unwrap();
+    debugger.command("continue");
+    assert!(matches!(debugger.runtime().status(), Status::Halted(5)));
+    let lines = output.contents().lines().map(|line| line.split(" by ").next().unwrap().to_string()).collect::<Vec<String>>();
+    assert_eq!(lines, [
+        "Watchpoint 2: R0 changed from null to 0",
+        "Breakpoint 1 at square (line 2): MOV result, n",
+        "Watchpoint 2: R0 changed from 0 to 4",
+        "Breakpoint 1 at square (line 2): MOV result, n",
+        "Watchpoint 2: R0 changed from 4 to 5",
+        "BREAKPOINT at main (line 10): BREAKPOINT",
+    ]);
+}
@@ -0,0 +1,41 @@
+use xasm::{Data, ErrorKind};
+
+mod common;
+use common::run;
+
+const HANDLERS: &str = "fun outer_handler\n  MOV P0, \"outer \"\n  JMP print\n  MOV P0, RET0\n  JMP print\n  MOV P0, \" \"\n  JMP print\n  MOV P0, RET2\n  JMP printline\nend\n\nfun inner_handler\n  MOV P0, \"inner \"\n  JMP print\n  MOV P0, RET2\n  JMP printline\n  THROW RET2\nend\n\nfun quiet_handler\n  MOV P0, \"quiet\"\n  JMP printline\nend\n\n";
+
+#[test]
+fn nested_try_regions_catch_innermost_first() {
+    let code = format!("{}fun main\n  TRY outer_handler\n    TRY quiet_handler\n      THROW 1\n    ENDTRY\n    MOV P0, \"after inner\"\n    JMP printline\n  ENDTRY\n  MOV P0, \"end\"\n  JMP printline\nend\n", HANDLERS);
+    let captured = run(&code, "");
+    assert_eq!(captured.output, "quiet\nafter inner\nend\n");
+    assert_eq!(captured.result.unwrap(), 0);
+}
+
+#[test]
+fn handlers_can_rethrow_to_the_enclosing_region() {
+    let code = format!("{}fun main\n  TRY outer_handler\n    TRY inner_handler\n      THROW 2\n      MOV P0, \"skipped\"\n      JMP printline\n    ENDTRY\n    MOV P0, \"skipped too\"\n    JMP printline\n  ENDTRY\n  MOV P0, \"end\"\n  JMP printline\nend\n", HANDLERS);
+    let captured = run(&code, "");
+    assert_eq!(captured.output, "inner 2\nouter Exception 2\nend\n");
+    assert_eq!(captured.result.unwrap(), 0);
+}
+
+#[test]
+fn a_rethrow_without_an_enclosing_region_is_uncaught() {
+    let code = format!("{}fun fails\n  THROW \"bad\"\nend\n\nfun main\n  TRY inner_handler\n    JMP fails\n  ENDTRY\nend\n", HANDLERS);
+    let captured = run(&code, "");
+    assert_eq!(captured.output, "inner bad\n");
+    let error = captured.result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Thrown);
+    assert!(matches!(error.value, Data::String(ref value) if value == "bad"));
+    assert_eq!(error.backtrace[0], "inner_handler: THROW RET2");
+}
+
+#[test]
+fn runtime_faults_in_called_functions_are_caught_by_the_caller() {
+    let code = format!("fun divide a b -> result\n  MOV result, a\n  DIV result, b\nend\n\n{}fun main\n  TRY outer_handler\n    CALL divide 1, 0\n  ENDTRY\n  TRY outer_handler\n    POP R0\n  ENDTRY\n  CALL divide 6, 3\n  MOV P0, RET0\n  JMP printline\nend\n", HANDLERS);
+    let captured = run(&code, "");
+    assert_eq!(captured.output, "outer DivideByZero null\nouter StackError null\n2\n");
+    assert_eq!(captured.result.unwrap(), 0);
+}
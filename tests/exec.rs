@@ -0,0 +1,68 @@
+#![cfg(unix)]
+
+use xasm::{Data, ErrorKind, RunTime};
+
+fn exec_runtime() -> RunTime {
+    let mut runtime = RunTime::default();
+    runtime.allow_exec(true);
+    runtime
+}
+
+fn set_args(runtime: &mut RunTime, args: &[&str]) {
+    runtime.set_register("P1", Data::List(args.iter().map(|arg| Data::String(arg.to_string())).collect())).unwrap();
+}
+
+fn ret(runtime: &RunTime, reg: &str) -> String {
+    runtime.register(reg).unwrap().to_string()
+}
+
+#[test]
+fn exec_is_denied_unless_allowed() {
+    let mut runtime = RunTime::default();
+    runtime.execute("MOV P0, \"echo\"").unwrap();
+    let error = runtime.execute("JMP exec").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Permission);
+    assert_eq!(error.message, "[exec] Running echo is not allowed, use --allow-exec");
+}
+
+#[test]
+fn exec_returns_the_status_and_output() {
+    let mut runtime = exec_runtime();
+    runtime.execute("MOV P0, \"echo\"").unwrap();
+    set_args(&mut runtime, &["hello", "from exec"]);
+    runtime.execute("JMP exec").unwrap();
+    assert!(matches!(runtime.register("RET0"), Some(Data::Int(0))));
+    assert_eq!(ret(&runtime, "RET1"), "hello from exec\n");
+    assert_eq!(ret(&runtime, "RET2"), "");
+
+    runtime.execute("MOV P0, \"sh\"").unwrap();
+    set_args(&mut runtime, &["-c", "echo oops >&2; exit 3"]);
+    runtime.execute("JMP exec").unwrap();
+    assert!(matches!(runtime.register("RET0"), Some(Data::Int(3))));
+    assert_eq!(ret(&runtime, "RET1"), "");
+    assert_eq!(ret(&runtime, "RET2"), "oops\n");
+}
+
+#[test]
+fn exec_writes_p2_to_the_standard_input() {
+    let mut runtime = exec_runtime();
+    runtime.execute("MOV P0, \"tr\"").unwrap();
+    set_args(&mut runtime, &["a-z", "A-Z"]);
+    runtime.execute("MOV P2, \"shout this\"").unwrap();
+    runtime.execute("JMP exec").unwrap();
+    assert_eq!(ret(&runtime, "RET1"), "SHOUT THIS");
+}
+
+#[test]
+fn exec_reports_bad_arguments_and_missing_programs() {
+    let mut runtime = exec_runtime();
+    runtime.execute("MOV P0, \"echo\"").unwrap();
+    runtime.execute("MOV P1, 5").unwrap();
+    let error = runtime.execute("JMP exec").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Type);
+    assert_eq!(error.message, "[exec] Expected a list of arguments or null in P1, got: 5");
+
+    runtime.execute("MOV P0, \"xasm-no-such-program\"").unwrap();
+    set_args(&mut runtime, &[]);
+    assert_eq!(runtime.execute("JMP exec").unwrap_err().kind, ErrorKind::Io);
+}
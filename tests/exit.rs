@@ -0,0 +1,60 @@
+use std::io::Cursor;
+
+use xasm::{ErrorKind, RunTime, SharedBuffer};
+
+mod common;
+use common::run;
+
+const HANDLERS: &str = "fun first\n  MOV P0, \"first\"\n  JMP printline\nend\n\nfun second\n  MOV P0, \"second\"\n  JMP printline\nend\n\nfun exit_again\n  MOV P0, 4\n  JMP exit\nend\n\n";
+
+#[test]
+fn exit_handlers_run_newest_first() {
+    let code = format!("{}fun main\n  MOV P0, &first\n  JMP atexit\n  MOV P0, &second\n  JMP atexit\n  MOV RET0, 2\nend\n", HANDLERS);
+    let captured = run(&code, "");
+    assert_eq!(captured.output, "second\nfirst\n");
+    assert_eq!(captured.result.unwrap(), 2);
+}
+
+#[test]
+fn exit_skips_the_rest_of_the_program_but_not_the_handlers() {
+    let code = format!("{}fun stop\n  MOV P0, 3\n  JMP exit\nend\n\nfun main\n  MOV P0, &first\n  JMP atexit\n  CALL stop\n  MOV P0, \"never printed\"\n  JMP printline\nend\n", HANDLERS);
+    let captured = run(&code, "");
+    assert_eq!(captured.output, "first\n");
+    assert_eq!(captured.result.unwrap(), 3);
+
+    //a handler calling exit changes the code, the remaining handlers still run
+    let code = format!("{}fun main\n  MOV P0, &first\n  JMP atexit\n  MOV P0, &exit_again\n  JMP atexit\n  MOV P0, 3\n  JMP exit\nend\n", HANDLERS);
+    let captured = run(&code, "");
+    assert_eq!(captured.output, "first\n");
+    assert_eq!(captured.result.unwrap(), 4);
+}
+
+#[test]
+fn exit_handlers_do_not_run_after_an_uncaught_error() {
+    let code = format!("{}fun main\n  MOV P0, &first\n  JMP atexit\n  POP R0\nend\n", HANDLERS);
+    let captured = run(&code, "");
+    assert_eq!(captured.output, "");
+    assert_eq!(captured.result.unwrap_err().kind, ErrorKind::Stack);
+}
+
+#[test]
+fn exit_and_atexit_check_their_parameter() {
+    let error = run("fun main\n  MOV P0, \"x\"\n  JMP exit\nend\n", "").result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Type);
+    assert_eq!(error.message, "[exit] Attempted to exit with non-integer code: x");
+    let error = run("fun main\n  MOV P0, 1\n  JMP atexit\nend\n", "").result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Type);
+    assert_eq!(error.message, "[atexit] Attempted to register non-function as exit handler: 1");
+}
+
+#[test]
+fn the_exit_message_is_only_printed_when_verbose() {
+    let code = "fun main\n  MOV P0, 6\n  JMP exit\nend\n";
+    assert_eq!(run(code, "").output, "");
+
+    let output = SharedBuffer::default();
+    let mut runtime = RunTime::with_streams(code, Cursor::new(""), output.clone(), SharedBuffer::default()).unwrap();
+    runtime.set_verbose(true);
+    assert_eq!(runtime.run(&[]).unwrap(), 6);
+    assert_eq!(output.contents(), "Process exited with code '6'\n");
+}
@@ -0,0 +1,54 @@
+use xasm::{Data, ErrorKind, RunTime};
+
+fn execute_all(runtime: &mut RunTime, instructions: &[&str]) {
+    for instruction in instructions {
+        runtime.execute(instruction).unwrap();
+    }
+}
+
+#[test]
+fn files_are_written_read_and_deleted_through_handles() {
+    let dir = std::env::temp_dir().join(format!("xasm-files-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("data.txt").display().to_string();
+    let mut runtime = RunTime::default();
+    runtime.allow_read(dir.to_str().unwrap()).unwrap();
+    runtime.allow_write(dir.to_str().unwrap()).unwrap();
+
+    execute_all(&mut runtime, &[&format!("MOV P0, \"{}\"", path), "MOV P1, \"w\"", "JMP file_open", "MOV R0, RET0", "MOV P0, R0", "MOV P1, \"one\\n\"", "JMP file_write", "JMP file_close"]);
+    execute_all(&mut runtime, &[&format!("MOV P0, \"{}\"", path), "MOV P1, \"two\\n\"", "JMP file_append", "JMP file_exists"]);
+    assert!(matches!(runtime.register("RET0"), Some(Data::Bool(true))));
+
+    execute_all(&mut runtime, &["MOV P1, \"r\"", "JMP file_open", "MOV P0, RET0", "JMP file_read_line"]);
+    assert_eq!(runtime.register("RET0").unwrap().to_string(), "one");
+    execute_all(&mut runtime, &["JMP file_read_line"]);
+    assert_eq!(runtime.register("RET0").unwrap().to_string(), "two");
+    assert!(!runtime.flags().eof);
+    execute_all(&mut runtime, &["JMP file_read_line"]);
+    assert!(matches!(runtime.register("RET0"), Some(Data::Null)));
+    assert!(runtime.flags().eof);
+
+    let error = runtime.execute("JMP file_write").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Io);
+    assert_eq!(error.message, "[file_write] File was not opened for writing");
+    execute_all(&mut runtime, &["JMP file_close"]);
+    assert!(runtime.execute("JMP file_read_line").is_err());
+
+    execute_all(&mut runtime, &[&format!("MOV P0, \"{}\"", dir.display()), "JMP dir_list"]);
+    assert_eq!(runtime.register("RET0").unwrap().to_string(), "[\"data.txt\"]");
+    execute_all(&mut runtime, &[&format!("MOV P0, \"{}\"", path), "JMP file_delete", "JMP file_exists"]);
+    assert!(matches!(runtime.register("RET0"), Some(Data::Bool(false))));
+    assert_eq!(runtime.execute("JMP file_delete").unwrap_err().kind, ErrorKind::Io);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn file_open_rejects_unknown_modes() {
+    let mut runtime = RunTime::default();
+    runtime.allow_read(".").unwrap();
+    runtime.allow_write(".").unwrap();
+    execute_all(&mut runtime, &["MOV P0, \"Cargo.toml\"", "MOV P1, \"x\""]);
+    let error = runtime.execute("JMP file_open").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Argument);
+    assert_eq!(error.message, "[file_open] Unknown mode x, expected r, w or a");
+}
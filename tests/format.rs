@@ -0,0 +1,86 @@
+use xasm::{Data, ErrorKind, RunTime};
+
+mod common;
+use common::run;
+
+fn format(spec: &str, value: &str) -> Result<String, String> {
+    let mut runtime = RunTime::default();
+    runtime.set_register("P0", Data::String(spec.to_string())).unwrap();
+    runtime.execute(&format!("MOV P1, {}", value)).unwrap();
+    match runtime.execute("JMP format") {
+        Ok(()) => Ok(runtime.register("RET0").unwrap().to_string()),
+        Err(error) => {
+            assert_eq!(error.kind, ErrorKind::Argument);
+            Err(error.message)
+        }
+    }
+}
+
+#[test]
+fn pads_to_the_width() {
+    assert_eq!(format("[{:5}]", "42").unwrap(), "[   42]");
+    assert_eq!(format("[{:5}]", "\"ab\"").unwrap(), "[ab   ]");
+    assert_eq!(format("[{:05}]", "-42").unwrap(), "[-0042]");
+    assert_eq!(format("[{:05}]", "1.5").unwrap(), "[001.5]");
+    assert_eq!(format("[{:2}]", "12345").unwrap(), "[12345]");
+}
+
+#[test]
+fn aligns_with_a_fill() {
+    assert_eq!(format("[{:<5}]", "42").unwrap(), "[42   ]");
+    assert_eq!(format("[{:>5}]", "\"ab\"").unwrap(), "[   ab]");
+    assert_eq!(format("[{:*^7}]", "\"hi\"").unwrap(), "[**hi***]");
+    assert_eq!(format("[{:->4}]", "7").unwrap(), "[---7]");
+}
+
+#[test]
+fn applies_the_precision() {
+    assert_eq!(format("{:.2}", "3.14159").unwrap(), "3.14");
+    assert_eq!(format("{:.1}", "2").unwrap(), "2.0");
+    assert_eq!(format("{:.3}", "\"truncated\"").unwrap(), "tru");
+    assert_eq!(format("[{:8.3}]", "-1.5").unwrap(), "[  -1.500]");
+}
+
+#[test]
+fn shows_the_sign_of_numbers() {
+    assert_eq!(format("{:+}", "7").unwrap(), "+7");
+    assert_eq!(format("{:+}", "-7").unwrap(), "-7");
+    assert_eq!(format("{:+.1}", "0.25").unwrap(), "+0.2");
+    assert_eq!(format("[{:+05}]", "42").unwrap(), "[+0042]");
+    assert_eq!(format("{:+}", "\"text\"").unwrap(), "text");
+}
+
+#[test]
+fn formats_integers_in_other_bases() {
+    assert_eq!(format("{:x}", "255").unwrap(), "ff");
+    assert_eq!(format("{:X}", "255").unwrap(), "FF");
+    assert_eq!(format("{:o}", "8").unwrap(), "10");
+    assert_eq!(format("{:#>6b}", "5").unwrap(), "###101");
+}
+
+#[test]
+fn rejects_bad_specs() {
+    assert_eq!(format("{:99999999999}", "1").unwrap_err(), "[format] Invalid width in {:99999999999}, it can be at most 65536");
+    assert_eq!(format("{:.99999999999}", "1.5").unwrap_err(), "[format] Invalid precision in {:.99999999999}, it can be at most 65536");
+    assert_eq!(format("{:.}", "1.5").unwrap_err(), "[format] Invalid precision in {:.}, it can be at most 65536");
+    assert_eq!(format("{:q}", "1").unwrap_err(), "[format] Unknown format type in {:q}");
+    assert_eq!(format("{:x}", "1.5").unwrap_err(), "[format] {:x} expects an integer, got: 1.5");
+    assert_eq!(format("{name}", "1").unwrap_err(), "[format] Invalid placeholder {name}, expected {} or {:spec}");
+    assert_eq!(format("{:5", "1").unwrap_err(), "[format] Unclosed placeholder in format string: {:5");
+    assert_eq!(format("}", "1").unwrap_err(), "[format] Unmatched } in format string: }");
+    assert_eq!(format("[{:65536}]", "1").unwrap().len(), 65538);
+}
+
+#[test]
+fn printf_takes_its_arguments_from_the_parameter_registers() {
+    let code = "fun main\n  MOV P0, \"x = {}, y = {:>3}, {{literal}}\\n{}|\"\n  MOV P1, 5\n  MOV P2, 7\n  MOV P3, \"end\"\n  JMP printf\nend\n";
+    let captured = run(code, "");
+    assert_eq!(captured.output, "x = 5, y =   7, {literal}\nend|");
+    assert_eq!(captured.result.unwrap(), 0);
+
+    //unset parameters are null, only running out of registers is an error
+    assert_eq!(run("fun main\n  MOV P0, \"{} {}\"\n  MOV P1, 1\n  JMP printf\nend\n", "").output, "1 null");
+    let error = run(".registers 2\n\nfun main\n  MOV P0, \"{} {} {}\"\n  JMP printf\nend\n", "").result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Argument);
+    assert_eq!(error.message, "[printf] Not enough arguments for format string: {} {} {}");
+}
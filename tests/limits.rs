@@ -0,0 +1,110 @@
+use std::io::Cursor;
+use std::time::Duration;
+
+use xasm::{ErrorKind, Limits, RunTime, SharedBuffer};
+
+mod common;
+use common::run_limited;
+
+const SPIN: &str = "fun spin\n  INC R0\nend\n\nfun main\n  MOV R0, 0\n  TRY handler\n  MOV L0, 1000000000\n  LOOPNODEC spin\nend\n\nfun handler\n  MOV P0, \"caught\"\n  JMP printline\nend\n";
+
+#[test]
+fn instruction_limit_cannot_be_caught() {
+    let (result, output) = run_limited(SPIN, Limits { instructions: Some(1000), ..Limits::default() });
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::LimitExceeded);
+    assert_eq!(error.message, "[limit] Exceeded the limit of 1000 instructions");
+    assert_eq!(error.backtrace, vec!["spin: INC R0", "main: LOOPNODEC spin"]);
+    assert_eq!(output, "");
+}
+
+#[test]
+fn time_limit_stops_endless_loops() {
+    let (result, _) = run_limited(SPIN, Limits { time: Some(Duration::from_millis(50)), ..Limits::default() });
+    assert_eq!(result.unwrap_err().message, "[limit] Exceeded the time limit of 50ms");
+}
+
+#[test]
+fn runaway_recursion_hits_the_call_depth_limit() {
+    let (result, _) = run_limited("fun main\n  JMP main\nend\n", Limits { call_depth: Some(100), ..Limits::default() });
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::LimitExceeded);
+    assert_eq!(error.backtrace.len(), 100);
+}
+
+#[test]
+fn pushing_past_the_stack_limit_fails() {
+    let code = "fun push_one\n  PUSH 1\nend\n\nfun main\n  MOV L0, 100\n  LOOP push_one\nend\n";
+    let (result, _) = run_limited(code, Limits { stack_depth: Some(10), ..Limits::default() });
+    assert_eq!(result.unwrap_err().message, "[limit] Exceeded the limit of 10 values on the stack");
+}
+
+#[test]
+fn the_smaller_of_max_stack_and_the_stack_directive_wins() {
+    let code = ".stack 5\n\nfun push_one\n  PUSH 1\nend\n\nfun main\n  MOV L0, 100\n  LOOP push_one\nend\n";
+    let (result, _) = run_limited(code, Limits { stack_depth: Some(10), ..Limits::default() });
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::StackOverflow);
+    assert_eq!(error.message, "[push] Stack overflow, exceeded maximum stack size of 5");
+
+    for limit in [3, 5] {
+        let (result, _) = run_limited(code, Limits { stack_depth: Some(limit), ..Limits::default() });
+        let error = result.unwrap_err();
+        assert_eq!(error.kind, ErrorKind::LimitExceeded);
+        assert_eq!(error.message, format!("[limit] Exceeded the limit of {} values on the stack", limit));
+    }
+}
+
+#[test]
+fn growing_strings_hit_the_value_size_limit() {
+    let code = "fun grow\n  ADD R0, R0\nend\n\nfun main\n  MOV R0, \"ab\"\n  MOV L0, 100\n  LOOP grow\nend\n";
+    let (result, _) = run_limited(code, Limits { value_size: Some(1000), ..Limits::default() });
+    assert_eq!(result.unwrap_err().message, "[limit] R0 exceeded the size limit of 1000");
+
+    let (result, _) = run_limited("fun main\n  CALL format \"{}{}\", \"abc\", \"abc\"\nend\n", Limits { value_size: Some(5), ..Limits::default() });
+    assert_eq!(result.unwrap_err().message, "[limit] [format] The result exceeded the size limit of 5");
+}
+
+#[test]
+fn values_are_checked_before_they_are_built() {
+    let limits = Limits { value_size: Some(10), ..Limits::default() };
+    let (result, _) = run_limited("fun main\n  CALL format \"{:60000}\", 1\nend\n", limits);
+    assert_eq!(result.unwrap_err().message, "[limit] [format] The result exceeded the size limit of 10");
+
+    let mut runtime = RunTime::with_streams("fun main\n  JMP read_all\nend\n", Cursor::new("x".repeat(100)), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+    runtime.set_limits(limits);
+    assert_eq!(runtime.run(&[]).unwrap_err().message, "[limit] [read_all] The result exceeded the size limit of 10");
+
+    let mut runtime = RunTime::with_streams("fun main\n  JMP fill\nend\n", Cursor::new(""), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+    runtime.set_limits(limits);
+    runtime.register_native("fill", |runtime| runtime.set_register("R7", xasm::Data::String("x".repeat(100))));
+    assert_eq!(runtime.run(&[]).unwrap_err().message, "[limit] R7 exceeded the size limit of 10");
+}
+
+#[test]
+fn blocking_builtins_stop_at_the_time_limit() {
+    let limits = Limits { time: Some(Duration::from_millis(100)), ..Limits::default() };
+    let started = std::time::Instant::now();
+    let (result, _) = run_limited("fun main\n  MOV P0, 3000\n  JMP sleep\nend\n", limits);
+    assert_eq!(result.unwrap_err().message, "[limit] Exceeded the time limit of 100ms");
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    #[cfg(unix)]
+    {
+        let code = "data\n  args [\"3\"]\nend\n\nfun main\n  MOV P0, \"sleep\"\n  MOV P1, args\n  JMP exec\nend\n";
+        let mut runtime = RunTime::with_streams(code, Cursor::new(""), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+        runtime.set_limits(limits);
+        runtime.allow_exec(true);
+        let started = std::time::Instant::now();
+        assert_eq!(runtime.run(&[]).unwrap_err().message, "[limit] Exceeded the time limit of 100ms");
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
+
+#[test]
+fn programs_within_their_limits_run_normally() {
+    let limits = Limits { instructions: Some(100), time: Some(Duration::from_secs(5)), stack_depth: Some(4), call_depth: Some(4), value_size: Some(64) };
+    let (result, output) = run_limited("fun main\n  MOV P0, \"fine\"\n  JMP printline\n  MOV RET0, 0\nend\n", limits);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(output, "fine\n");
+}
@@ -0,0 +1,64 @@
+use xasm::ErrorKind;
+
+mod common;
+use common::run;
+
+#[test]
+fn nested_loops_restore_the_outer_counter() {
+    let code = "fun main\n  MOV R0, 0\n  MOV L0, 3\n  LOOP outer\n  MOV RET0, R0\nend\n\nfun outer\n  MOV P0, L0\n  JMP print\n  MOV L0, 2\n  LOOP inner\nend\n\nfun inner\n  INC R0\nend\n";
+    let captured = run(code, "");
+    assert_eq!(captured.output, "321");
+    assert_eq!(captured.result.unwrap(), 6);
+}
+
+#[test]
+fn loop_decrements_what_the_body_left_in_l0() {
+    let code = "fun main\n  MOV L0, 5\n  LOOP body\n  CALL printline L0\nend\n\nfun body\n  CALL printline L0\n  MOV L0, 0\nend\n";
+    let captured = run(code, "");
+    assert_eq!(captured.output, "5\n-1\n");
+    assert_eq!(captured.result.unwrap(), 0);
+
+    let code = "fun main\n  MOV R0, 0\n  MOV L0, 2\n  LOOP body\n  MOV RET0, R0\nend\n\nfun body\n  INC R0\n  CMP R0, 1\n  JE again\nend\n\nfun again\n  MOV L0, 4\nend\n";
+    assert_eq!(run(code, "").result.unwrap(), 4);
+
+    let error = run("fun main\n  MOV L0, 2\n  LOOP body\nend\n\nfun body\n  MOV L0, \"x\"\nend\n", "").result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Type);
+    assert_eq!(error.message, "[loop] Attempted to loop with non-integer value");
+}
+
+#[test]
+fn break_stops_the_innermost_loop() {
+    let code = "fun main\n  MOV R0, 0\n  FOR R1, 0, 3, 1, outer\n  MOV RET0, R0\nend\n\nfun outer\n  FOR R2, 0, 100, 1, inner\nend\n\nfun inner\n  CMP R2, 4\n  JE stop\n  INC R0\nend\n\nfun stop\n  BREAK\nend\n";
+    assert_eq!(run(code, "").result.unwrap(), 12);
+}
+
+#[test]
+fn continue_inside_try_skips_the_iteration() {
+    let code = "fun main\n  MOV R0, 0\n  FOR R1, 0, 5, 1, body\n  MOV P0, R0\n  JMP print\n  POP R9\nend\n\nfun body\n  TRY handler\n  CMP R1, 2\n  JE skip\n  ADD R0, R1\n  ENDTRY\nend\n\nfun skip\n  CONTINUE\nend\n\nfun handler\n  MOV R0, -1\nend\n";
+    let captured = run(code, "");
+    assert_eq!(captured.output, "8");
+    assert_eq!(captured.result.unwrap_err().kind, ErrorKind::Stack); //the handler is gone once the loop is over
+}
+
+#[test]
+fn for_counts_down_with_a_negative_step() {
+    let code = "fun main\n  FOR R0, 5, 0, -2, body\nend\n\nfun body\n  MOV P0, R0\n  JMP print\nend\n";
+    let captured = run(code, "");
+    assert_eq!(captured.output, "531");
+    assert_eq!(captured.result.unwrap(), 0);
+}
+
+#[test]
+fn for_rejects_a_step_of_zero() {
+    let error = run("fun main\n  FOR R0, 0, 5, 0, body\nend\n\nfun body\nend\n", "").result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Argument);
+    assert_eq!(error.message, "[for] Attempted to loop with a step of 0");
+}
+
+#[test]
+fn for_ends_when_the_index_would_overflow() {
+    let code = "fun main\n  MOV R1, 0\n  FOR R0, 0, 9223372036854775807, 4611686018427387904, body\n  MOV RET0, R1\nend\n\nfun body\n  INC R1\nend\n";
+    assert_eq!(run(code, "").result.unwrap(), 2);
+    let code = "fun main\n  MOV R1, 0\n  FOR R0, 0, -9223372036854775808, -4611686018427387904, body\n  MOV RET0, R1\nend\n\nfun body\n  INC R1\nend\n";
+    assert_eq!(run(code, "").result.unwrap(), 2);
+}
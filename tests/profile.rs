@@ -0,0 +1,97 @@
+use std::io::Cursor;
+
+use xasm::{Profiler, RunTime, SharedBuffer};
+
+const PROGRAM: &str = "fun square n -> result
+  MOV result, n
+  MUL result, n
+end
+
+fun main
+  MOV R0, 0
+  MOV L0, 3
+  LOOP add_square
+  MOV RET0, R0
+end
+
+fun add_square
+  CALL square L0
+  ADD R0, RET0
+end
+";
+
+fn profile() -> Profiler {
+    let mut runtime = RunTime::with_streams(PROGRAM, Cursor::new(""), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+    let mut profiler = Profiler::new();
+    runtime.start(&[]).unwrap();
+    assert_eq!(profiler.run(&mut runtime).unwrap(), 14);
+    profiler
+}
+
+//the columns of the report line that starts with the given words, without the times
+fn counts(report: &str, start: &str) -> Vec<String> {
+    let line = report.lines().find(|line| line.trim_start().starts_with(start)).unwrap();
+    line.split_whitespace().filter(|column| !column.contains('.')).map(String::from).collect()
+}
+
+#[test]
+fn calls_and_instructions_are_counted_per_function_and_line() {
+    let report = profile().report();
+    assert!(report.starts_with("Profiled 16 instructions in "));
+    assert_eq!(counts(&report, "main "), ["main", "1", "4"]);
+    assert_eq!(counts(&report, "add_square "), ["add_square", "3", "6"]);
+    assert_eq!(counts(&report, "square "), ["square", "3", "6"]);
+    assert_eq!(counts(&report, "14 "), ["14", "add_square", "3"]);
+    assert_eq!(counts(&report, "7 "), ["7", "main", "1"]);
+}
+
+#[test]
+fn folded_stacks_name_every_caller() {
+    let folded = profile().folded();
+    let stacks = folded.lines().map(|line| line.rsplit_once(' ').unwrap().0).collect::<Vec<&str>>();
+    assert_eq!(stacks, ["main", "main;add_square", "main;add_square;square"]);
+    assert!(folded.lines().all(|line| line.rsplit_once(' ').unwrap().1.parse::<u64>().is_ok()));
+}
+
+#[test]
+fn a_recursive_call_made_again_by_a_handler_is_a_new_call() {
+    //down calls itself until R0 is 3 and fails there, then the TRY handler of main calls down again at the same depth
+    let code = "fun main\n  TRY down\n  CALL down\n  ENDTRY\nend\n\nfun down\n  INC R0\n  CMP R0, 3\n  JE fail\n  SETL R1\n  CMP R1, true\n  JE down\nend\n\nfun fail\n  THROW \"bottom\"\nend\n";
+    let mut runtime = RunTime::with_streams(code, Cursor::new(""), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+    runtime.set_register("R0", xasm::Data::Int(0)).unwrap();
+    let mut profiler = Profiler::new();
+    runtime.start(&[]).unwrap();
+    assert_eq!(profiler.run(&mut runtime).unwrap(), 0);
+
+    let report = profiler.report();
+    assert_eq!(counts(&report, "down "), ["down", "4", "21"]);
+    assert_eq!(counts(&report, "fail "), ["fail", "1", "1"]);
+    assert_eq!(counts(&report, "main "), ["main", "1", "2"]);
+    let inclusive = |start: &str| report.lines().find(|line| line.starts_with(start)).unwrap().split_whitespace().nth(3).unwrap().parse::<f64>().unwrap();
+    assert!(inclusive("down ") <= inclusive("main "));
+}
+
+#[test]
+fn native_functions_are_leaf_calls_with_their_own_time() {
+    let code = "fun main\n  CALL wait\n  CALL outer\n  MOV P0, \"done\"\n  JMP print\nend\n\nfun outer\n  JMP wait\nend\n";
+    let mut runtime = RunTime::with_streams(code, Cursor::new(""), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+    runtime.register_native("wait", |_| {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        Ok(())
+    });
+    let mut profiler = Profiler::new();
+    runtime.start(&[]).unwrap();
+    assert_eq!(profiler.run(&mut runtime).unwrap(), 0);
+
+    let report = profiler.report();
+    assert_eq!(counts(&report, "wait "), ["wait", "2", "0"]);
+    assert_eq!(counts(&report, "print "), ["print", "1", "0"]);
+    assert_eq!(counts(&report, "main "), ["main", "1", "4"]);
+    let exclusive = |start: &str| report.lines().find(|line| line.starts_with(start)).unwrap().split_whitespace().nth(4).unwrap().parse::<f64>().unwrap();
+    assert!(exclusive("wait ") >= 40.0);
+    assert!(exclusive("main ") < 20.0 && exclusive("outer ") < 20.0);
+
+    let folded = profiler.folded();
+    let stacks = folded.lines().map(|line| line.rsplit_once(' ').unwrap().0).collect::<Vec<&str>>();
+    assert_eq!(stacks, ["main", "main;outer", "main;outer;wait", "main;print", "main;wait"]);
+}
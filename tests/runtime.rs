@@ -0,0 +1,300 @@
+use std::io::Cursor;
+
+use xasm::{Data, ErrorKind, RunTime, SharedBuffer};
+
+mod common;
+use common::run;
+
+#[test]
+fn captures_printed_output() {
+    let captured = run("fun main\n  MOV P0, \"Hello, World!\"\n  JMP printline\nend\n", "");
+    assert_eq!(captured.result.unwrap(), 0);
+    assert_eq!(captured.output, "Hello, World!\n");
+    assert_eq!(captured.errors, "");
+}
+
+#[test]
+fn reads_typed_input_from_the_given_string() {
+    let code = "fun main\n  MOV P0, \"\"\n  JMP input_int\n  MOV R0, RET0\n  JMP input_int\n  JERR bad\n  ADD R0, 1\n  MOV P0, R0\n  JMP printline\nend\n\nfun bad\n  MOV P0, \"not a number\"\n  JMP printline\nend\n";
+    let captured = run(code, "41\nabc\n");
+    assert_eq!(captured.output, "not a number\n42\n");
+}
+
+#[test]
+fn prompts_are_only_shown_once() {
+    let code = "fun main\n  MOV P0, \"a? \"\n  JMP input_int\n  MOV R0, RET0\n  JMP input_int\n  ADD R0, RET0\n  MOV P0, R0\n  JMP printline\nend\n";
+    assert_eq!(run(code, "1\n2\n").output, "a? 3\n");
+}
+
+#[test]
+fn end_of_input_sets_the_eof_flag() {
+    let mut runtime = RunTime::with_streams("", Cursor::new("only line\n"), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+    runtime.execute("JMP input").unwrap();
+    assert_eq!(runtime.register("RET0").unwrap().to_string(), "only line");
+    assert!(!runtime.flags().eof);
+    runtime.execute("JMP input").unwrap();
+    assert!(matches!(runtime.register("RET0"), Some(Data::Null)));
+    assert!(runtime.flags().eof);
+}
+
+#[test]
+fn exit_code_comes_from_ret0_or_exit() {
+    assert_eq!(run("fun main\n  MOV RET0, 7\nend\n", "").result.unwrap(), 7);
+    assert_eq!(run("fun main\n  MOV P0, 3\n  JMP exit\n  MOV RET0, 7\nend\n", "").result.unwrap(), 3);
+    assert_eq!(run("fun main -> code\n  MOV code, 5\nend\n", "").result.unwrap(), 5);
+    for code in ["256", "-1", "4294967296"] {
+        let error = run(&format!("fun main\n  MOV P0, {}\n  JMP exit\nend\n", code), "").result.unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Argument);
+        assert_eq!(error.message, format!("[exit] Attempted to exit with a code outside 0..=255: {}", code));
+        let error = run(&format!("fun main\n  MOV RET0, {}\nend\n", code), "").result.unwrap_err();
+        assert_eq!(error.message, format!("[exit] The entry function returned an exit code outside 0..=255: {}", code));
+    }
+}
+
+#[test]
+fn ret0_left_by_builtins_and_calls_is_not_the_exit_code() {
+    assert_eq!(run("fun main\n  JMP time\nend\n", "").result.unwrap(), 0);
+    assert_eq!(run("fun main\n  JMP input_int\nend\n", "300\n").result.unwrap(), 0);
+    assert_eq!(run("fun main\n  CALL seven\nend\n\nfun seven\n  MOV RET0, 7\nend\n", "").result.unwrap(), 0);
+    assert_eq!(run("fun main\n  MOV RET0, 3\n  JMP time\nend\n", "").result.unwrap(), 3);
+    assert_eq!(run("fun main\n  CALL seven\n  MOV RET0, RET0\nend\n\nfun seven\n  MOV RET0, 7\nend\n", "").result.unwrap(), 7);
+}
+
+#[test]
+fn uncaught_errors_are_reported_with_a_backtrace() {
+    let captured = run("fun main\n  POP R0\nend\n", "");
+    let error = captured.result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Stack);
+    assert_eq!(captured.errors, "[pop] Attempted to pop from empty stack\nBacktrace:\n    at main: POP R0\n");
+}
+
+#[test]
+fn invalid_programs_fail_to_load() {
+    let error = RunTime::new("fun main ->\nend\n").unwrap_err();
+    assert_eq!(error.message, "Invalid function declaration: fun main ->");
+
+    let error = RunTime::new("data\n  limit 10\nend\n\nfun main\n  MOV limit, 5\nend\n").unwrap_err();
+    assert!(error.message.starts_with("Attempted to write to constant limit"));
+}
+
+#[test]
+fn constants_need_plain_names() {
+    for name in ["true", "false", "null", "5", "\"x\"", "&main", "a-b"] {
+        let error = RunTime::new(&format!("data\n  {} 5\nend\n", name)).unwrap_err();
+        assert_eq!(error.message, format!("Invalid constant name: {}", name));
+    }
+    let error = RunTime::new("data\n  printline \"x\"\nend\n").unwrap_err();
+    assert_eq!(error.message, "Constant printline shadows an existing register, alias or function");
+    let error = RunTime::new("data\n  R0 1\nend\n").unwrap_err();
+    assert_eq!(error.message, "Constant R0 shadows an existing register, alias or function");
+
+    let captured = run("data\n  _limit2 5\nend\n\nfun main\n  MOV RET0, _limit2\nend\n", "");
+    assert_eq!(captured.result.unwrap(), 5);
+}
+
+#[test]
+fn call_local_names_belong_to_their_call() {
+    //show cannot see the n of outer, and outer gets its own n back after calling itself
+    let code = "fun main\n  CALL outer 2\nend\n\nfun outer n\n  TRY missing\n  CALL show\n  ENDTRY\n  CMP n, 2\n  JE again\n  CALL print n\nend\n\nfun again\n  CALL outer 1\nend\n\nfun show\n  CALL print n\nend\n\nfun missing\n  CALL print \"-\"\nend\n";
+    let captured = run(code, "");
+    assert_eq!(captured.output, "--12");
+    assert_eq!(captured.result.unwrap(), 0);
+
+    let code = "fun main -> result\n  CALL sum 3\n  MOV result, RET0\nend\n\nfun sum n -> total\n  MOV total, n\n  CALL same 4\n  ADD total, RET0\nend\n\nfun same n -> total\n  MOV total, n\nend\n";
+    assert_eq!(run(code, "").result.unwrap(), 7);
+}
+
+#[test]
+fn parameters_cannot_shadow_other_names() {
+    let error = RunTime::new("fun main\nend\n\nfun f main\nend\n").unwrap_err();
+    assert_eq!(error.message, "Parameter main of function f shadows a function or alias");
+    let error = RunTime::new("fun f -> print\nend\n").unwrap_err();
+    assert_eq!(error.message, "Parameter print of function f shadows a function or alias");
+    let error = RunTime::new("data\n  limit 10\nend\n\nfun f limit\nend\n").unwrap_err();
+    assert_eq!(error.message, "Parameter limit of function f shadows a constant");
+    let error = RunTime::new("fun f R0\nend\n").unwrap_err();
+    assert_eq!(error.message, "Parameter R0 of function f shadows a register");
+}
+
+#[test]
+fn integer_overflow_raises_a_catchable_error() {
+    for (setup, instruction) in [("MOV R0, 9223372036854775807", "INC R0"), ("MOV R0, -9223372036854775808", "DEC R0"), ("MOV R0, 9223372036854775807", "ADD R0, 1"),
+        ("MOV R0, -9223372036854775808", "SUB R0, 1"), ("MOV R0, 4611686018427387904", "MUL R0, 2")] {
+        let mut runtime = RunTime::default();
+        runtime.execute(setup).unwrap();
+        let error = runtime.execute(instruction).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Overflow);
+        assert!(error.message.ends_with("The result does not fit in an integer"));
+    }
+    let mut runtime = RunTime::default();
+    runtime.execute("MOV R0, -9223372036854775808").unwrap();
+    runtime.execute("DIV R0, -1").unwrap(); //a float result, so it fits
+
+    let code = "fun main\n  MOV R1, 9223372036854775807\n  TRY handler\n  ADD R1, R1\n  ENDTRY\nend\n\nfun handler\n  CALL print RET0\nend\n";
+    let captured = run(code, "");
+    assert_eq!(captured.output, "OverflowError");
+    assert_eq!(captured.result.unwrap(), 0);
+}
+
+#[test]
+fn registers_can_be_set_and_inspected() {
+    let mut runtime = RunTime::default();
+    runtime.set_register("R0", Data::Int(40)).unwrap();
+    runtime.execute("ADD R0, 2").unwrap();
+    assert!(matches!(runtime.register("R0"), Some(Data::Int(42))));
+
+    let error = runtime.set_register("R999", Data::Int(1)).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Register);
+}
+
+#[test]
+fn values_pushed_by_the_host_can_be_popped() {
+    let mut runtime = RunTime::default();
+    runtime.push_stack(Data::Int(1)).unwrap();
+    runtime.push_stack(Data::String("two".to_string())).unwrap();
+    assert_eq!(runtime.stack().len(), 2);
+    runtime.execute("POP R1").unwrap();
+    assert_eq!(runtime.register("R1").unwrap().to_string(), "two");
+    assert!(matches!(runtime.register("SP"), Some(Data::Int(1))));
+}
+
+#[test]
+fn comparisons_set_the_flags() {
+    let mut runtime = RunTime::default();
+    runtime.execute("MOV R0, 5").unwrap();
+    runtime.execute("CMP R0, 3").unwrap();
+    let flags = runtime.flags();
+    assert!(flags.greater && !flags.lesser && !flags.equal);
+}
+
+#[test]
+fn aliases_declared_through_execute_resolve() {
+    let mut runtime = RunTime::default();
+    runtime.execute(".alias counter R3").unwrap();
+    runtime.execute("MOV counter, 9").unwrap();
+    assert!(matches!(runtime.register("R3"), Some(Data::Int(9))));
+    assert!(matches!(runtime.register("counter"), Some(Data::Int(9))));
+}
+
+#[test]
+fn aliases_resolve_in_field_paths() {
+    let code = ".alias pt R0\n.alias out R1\n\nstruct Point { x, y }\n\nfun main\n  NEW pt, Point\n  SETF pt.x, 4\n  GETF out, pt.x\n  MOV RET0, out\nend\n";
+    assert_eq!(run(code, "").result.unwrap(), 4);
+}
+
+#[test]
+fn aliases_are_checked_when_loading() {
+    let code = ".registers 16\n.alias counter R15\n\nfun main\n  MOV counter, 2\n  INC counter\n  MOV RET0, R15\nend\n";
+    assert_eq!(run(code, "").result.unwrap(), 3);
+
+    let error = RunTime::new(".alias counter R15\n").unwrap_err();
+    assert_eq!(error.message, "Alias counter refers to non-existant register: R15");
+    for name in ["R1", "main", "printline"] {
+        let error = RunTime::new(&format!(".alias {} R0\n\nfun main\nend\n", name)).unwrap_err();
+        assert_eq!(error.message, format!("Alias {} shadows an existing register or function", name));
+    }
+    let mut runtime = RunTime::default();
+    let error = runtime.execute(".alias print R0").unwrap_err();
+    assert_eq!(error.message, "Alias print shadows an existing register or function");
+    let error = RunTime::new(".alias counter\n").unwrap_err();
+    assert_eq!(error.message, "Invalid alias declaration: .alias counter");
+    let error = RunTime::new(".registers 0\n").unwrap_err();
+    assert_eq!(error.message, "Invalid register count directive: .registers 0");
+}
+
+#[test]
+fn the_register_count_can_be_changed_after_loading() {
+    let mut runtime = RunTime::new(".registers 2\n").unwrap();
+    assert!(runtime.register("R2").is_none());
+    runtime.set_register_count(20).unwrap();
+    assert!(matches!(runtime.register("RET19"), Some(Data::Null)));
+    runtime.set_register_count(1).unwrap();
+    assert!(runtime.register("P1").is_none());
+    assert!(runtime.register("R0").is_some());
+
+    let mut runtime = RunTime::new(".alias last R12\n").unwrap();
+    let error = runtime.set_register_count(5).unwrap_err();
+    assert_eq!(error.message, "Alias last refers to non-existant register: R12");
+    let mut runtime = RunTime::new(".registers 1\n\nfun f R3\nend\n").unwrap();
+    let error = runtime.set_register_count(4).unwrap_err();
+    assert_eq!(error.message, "Parameter R3 of function f shadows a register");
+    assert!(runtime.register("R3").is_none());
+}
+
+#[test]
+fn native_functions_are_called_like_builtins() {
+    let output = SharedBuffer::default();
+    let code = "fun main\n  CALL double 21\n  MOV P0, RET0\n  JMP printline\nend\n";
+    let mut runtime = RunTime::with_streams(code, Cursor::new(""), output.clone(), SharedBuffer::default()).unwrap();
+    runtime.register_native("double", |runtime| {
+        let Some(Data::Int(value)) = runtime.register("P0").cloned() else {
+            return Err(xasm::RuntimeError::new(ErrorKind::Type, "[double] Expected an integer".to_string()));
+        };
+        runtime.set_register("RET0", Data::Int(value * 2))
+    });
+    assert_eq!(runtime.run(&[]).unwrap(), 0);
+    assert_eq!(output.contents(), "42\n");
+}
+
+#[test]
+fn errors_from_native_functions_can_be_caught() {
+    let code = "fun main\n  TRY handler\n  JMP fail\nend\n\nfun handler\n  MOV P0, RET1\n  JMP printline\nend\n";
+    let output = SharedBuffer::default();
+    let mut runtime = RunTime::with_streams(code, Cursor::new(""), output.clone(), SharedBuffer::default()).unwrap();
+    runtime.register_native("fail", |_| Err(xasm::RuntimeError::new(ErrorKind::Io, "[fail] disk on fire".to_string())));
+    runtime.run(&[]).unwrap();
+    assert_eq!(output.contents(), "[fail] disk on fire\n");
+}
+
+#[test]
+fn file_access_is_denied_unless_allowed() {
+    let captured = run("fun main\n  MOV P0, \"Cargo.toml\"\n  MOV P1, \"r\"\n  JMP file_open\nend\n", "");
+    assert_eq!(captured.result.unwrap_err().kind, ErrorKind::Permission);
+
+    let output = SharedBuffer::default();
+    let code = "fun main\n  MOV P0, \"Cargo.toml\"\n  MOV P1, \"r\"\n  JMP file_open\n  MOV P0, RET0\n  JMP file_read_line\n  MOV P0, RET0\n  JMP printline\nend\n";
+    let mut runtime = RunTime::with_streams(code, Cursor::new(""), output.clone(), SharedBuffer::default()).unwrap();
+    runtime.allow_read(".").unwrap();
+    runtime.run(&[]).unwrap();
+    assert_eq!(output.contents(), "[package]\n");
+}
+
+#[test]
+fn program_arguments_are_passed_in_argv() {
+    let code = "fun main\n  GETI P0, ARGV, 1\n  JMP printline\n  MOV RET0, ARGC\nend\n";
+    let captured = RunTime::run_with_input(code, "", &["first".to_string(), "second".to_string()]).unwrap();
+    assert_eq!(captured.output, "second\n");
+    assert_eq!(captured.result.unwrap(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn dangling_symlinks_cannot_escape_the_sandbox() {
+    let root = std::env::temp_dir().join(format!("xasm-sandbox-{}", std::process::id()));
+    let sandbox = root.join("box");
+    std::fs::create_dir_all(&sandbox).unwrap();
+    let outside = root.join("outside_target");
+    std::os::unix::fs::symlink(&outside, sandbox.join("evil")).unwrap();
+
+    for (builtin, mode) in [("file_open", "\n  MOV P1, \"w\""), ("file_append", "\n  MOV P1, \"pwned\"")] {
+        let code = format!("fun main\n  MOV P0, \"{}\"{}\n  JMP {}\nend\n", sandbox.join("evil").display(), mode, builtin);
+        let mut runtime = RunTime::with_streams(&code, Cursor::new(""), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+        runtime.allow_write(sandbox.to_str().unwrap()).unwrap();
+        assert_eq!(runtime.run(&[]).unwrap_err().kind, ErrorKind::Permission);
+    }
+    assert!(!outside.exists());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn builtins_report_missing_parameter_registers() {
+    for builtin in ["file_open", "file_append", "setenv", "exec"] {
+        let code = format!(".registers 1\n\nfun main\n  MOV P0, \"out.txt\"\n  JMP {}\nend\n", builtin);
+        let mut runtime = RunTime::with_streams(&code, Cursor::new(""), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+        runtime.allow_write(".").unwrap();
+        runtime.allow_exec(true);
+        let error = runtime.run(&[]).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Argument);
+        assert_eq!(error.message, format!("[{}] Expected a value in P1, but the program has no register P1", builtin));
+    }
+}
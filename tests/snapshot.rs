@@ -0,0 +1,79 @@
+use std::io::Cursor;
+
+use xasm::{Data, RunTime, SharedBuffer, Status};
+
+#[test]
+fn every_value_survives_a_snapshot() {
+    let mut runtime = RunTime::new("struct Point { x, y }\n").unwrap();
+    runtime.execute("NEW R0, Point").unwrap();
+    runtime.execute("SETF R0.x, 1.5").unwrap();
+    runtime.set_register("R1", Data::List(vec![Data::Int(-3), Data::Bool(true), Data::Null, Data::Function("main".to_string())])).unwrap();
+    runtime.set_register("R2", Data::String("quote \" and\nnewline ✓".to_string())).unwrap();
+    runtime.set_register("R3", Data::Float(f64::INFINITY)).unwrap();
+    runtime.set_register("R4", Data::File(2)).unwrap();
+    runtime.set_register("R5", Data::Int(i64::MIN)).unwrap();
+    runtime.push_stack(Data::Float(0.1)).unwrap();
+    runtime.execute("CMP R5, 0").unwrap();
+    let snapshot = runtime.snapshot();
+
+    let mut restored = RunTime::default();
+    restored.restore(&snapshot).unwrap();
+    for reg in ["R0", "R1", "R2", "R3", "R4", "R5"] {
+        assert_eq!(format!("{:?}", restored.register(reg)), format!("{:?}", runtime.register(reg)));
+    }
+    assert_eq!(format!("{:?}", restored.stack()), "[Float(0.1)]");
+    assert_eq!(restored.flags(), runtime.flags());
+    assert_eq!(restored.snapshot(), snapshot);
+}
+
+#[test]
+fn a_restored_program_continues_where_it_was_saved() {
+    let code = "fun main\n  MOV R0, 0\n  MOV L0, 4\n  LOOP count\n  MOV RET0, R0\nend\n\nfun count\n  TRY handler\n  ADD R0, L0\n  MOV P0, R0\n  JMP printline\n  ENDTRY\nend\n\nfun handler\nend\n";
+    let output = SharedBuffer::default();
+    let mut runtime = RunTime::with_streams(code, Cursor::new(""), output.clone(), SharedBuffer::default()).unwrap();
+    runtime.start(&[]).unwrap();
+    runtime.run_for(12);
+    let snapshot = runtime.snapshot();
+    let printed = output.contents();
+
+    let resumed_output = SharedBuffer::default();
+    let mut resumed = RunTime::with_streams("", Cursor::new(""), resumed_output.clone(), SharedBuffer::default()).unwrap();
+    resumed.restore(&snapshot).unwrap();
+    assert!(matches!(resumed.status(), Status::Running));
+    assert_eq!(resumed.resume().unwrap(), 10);
+    assert_eq!(runtime.resume().unwrap(), 10);
+    assert_eq!(printed + &resumed_output.contents(), output.contents());
+}
+
+#[test]
+fn invalid_snapshots_leave_the_runtime_unchanged() {
+    let mut runtime = RunTime::default();
+    runtime.execute("MOV R0, 7").unwrap();
+    let error = runtime.restore("{\"version\": 1, \"entry\": ").unwrap_err();
+    assert!(error.message.starts_with("Invalid snapshot: "));
+    let error = runtime.restore(&runtime.snapshot().replace("\"version\": 1", "\"version\": 99")).unwrap_err();
+    assert_eq!(error.message, "Invalid snapshot: version 99 is not supported, expected 1");
+    assert!(matches!(runtime.register("R0"), Some(Data::Int(7))));
+
+    let mut looping = RunTime::new("fun main\n  MOV L0, 4\n  LOOP count\nend\n\nfun count\nend\n").unwrap();
+    looping.start(&[]).unwrap();
+    looping.run_for(3);
+    let snapshot = looping.snapshot();
+    let extra_loop = "\"tasks\": [\n    {\"loop\": \"loop\", \"label\": \"count\", \"counter\": 2, \"iterating\": true},";
+    let error = runtime.restore(&snapshot.replace("\"tasks\": [", extra_loop)).unwrap_err();
+    assert_eq!(error.message, "Invalid snapshot: the loops in tasks do not match loop_depth and loop_counters");
+    let error = runtime.restore(&snapshot.replace("\"P0\": null,", "")).unwrap_err();
+    assert_eq!(error.message, "Invalid snapshot: missing register P0");
+    assert!(matches!(runtime.register("R0"), Some(Data::Int(7))));
+}
+
+
+#[test]
+fn deeply_nested_snapshots_are_rejected() {
+    let mut runtime = RunTime::default();
+    let nested = "[".repeat(200_000) + &"]".repeat(200_000);
+    let error = runtime.restore(&nested).unwrap_err();
+    assert_eq!(error.message, "Invalid snapshot: Nesting too deep at byte 512, the limit is 512 levels");
+    let error = runtime.restore(&"{\"a\": ".repeat(1000)).unwrap_err();
+    assert!(error.message.starts_with("Invalid snapshot: Nesting too deep"));
+}
@@ -0,0 +1,75 @@
+use std::io::Cursor;
+
+use xasm::{Data, ErrorKind, RunTime, SharedBuffer};
+
+mod common;
+use common::run;
+
+//the stack from the bottom, as strings
+fn stack(runtime: &RunTime) -> Vec<String> {
+    runtime.stack().iter().map(|value| value.to_string()).collect()
+}
+
+#[test]
+fn stack_operations_rearrange_the_top_values() {
+    let mut runtime = RunTime::default();
+    for line in ["PUSH 1", "PUSH 2", "OVER", "SWAP", "DUP"] {
+        runtime.execute(line).unwrap();
+    }
+    assert_eq!(stack(&runtime), ["1", "1", "2", "2"]);
+    runtime.execute("DROP").unwrap();
+    runtime.execute("PEEK R0, 2").unwrap();
+    runtime.execute("DEPTH R1").unwrap();
+    assert_eq!(stack(&runtime), ["1", "1", "2"]);
+    assert!(matches!(runtime.register("R0"), Some(Data::Int(1))));
+    assert!(matches!(runtime.register("R1"), Some(Data::Int(3))));
+    assert!(matches!(runtime.register("SP"), Some(Data::Int(3))));
+}
+
+#[test]
+fn stack_operations_report_underflow() {
+    let cases = [
+        ("DUP", "[dup] Attempted to duplicate the top of an empty stack"),
+        ("SWAP", "[swap] Attempted to swap with less than 2 values on the stack"),
+        ("OVER", "[over] Attempted to copy the second value with less than 2 values on the stack"),
+        ("DROP", "[drop] Attempted to pop from empty stack"),
+        ("POP R0", "[pop] Attempted to pop from empty stack"),
+        ("PEEK R0, 1", "[peek] Attempted to peek 1 values deep into a stack of 1 values"),
+        ("PEEK R0, -1", "[peek] Attempted to peek -1 values deep into a stack of 1 values"),
+    ];
+    for (line, message) in cases {
+        let mut runtime = RunTime::default();
+        if !matches!(line, "DUP" | "DROP" | "POP R0") {
+            runtime.execute("PUSH 1").unwrap();
+        }
+        let error = runtime.execute(line).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Stack, "{}", line);
+        assert_eq!(error.message, message);
+    }
+}
+
+#[test]
+fn arguments_are_addressed_from_the_frame_pointer() {
+    let code = "fun add_args\n  LDS RET0, -2\n  LDS R0, -1\n  ADD RET0, R0\n  STS -1, 0\n  MOV R1, FP\nend\n\nfun main\n  PUSH 40\n  PUSH 2\n  JMP add_args\n  POP R2\n  MOV P0, RET0\n  JMP printline\n  MOV RET0, R1\nend\n";
+    let mut runtime = RunTime::with_streams(code, Cursor::new(""), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+    assert_eq!(runtime.run(&[]).unwrap(), 2);
+    assert!(matches!(runtime.register("R2"), Some(Data::Int(0))));
+    assert_eq!(stack(&runtime), ["40"]);
+
+    let error = run("fun main\n  PUSH 1\n  JMP read\nend\n\nfun read\n  LDS R0, 0\nend\n", "").result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Stack);
+    assert_eq!(error.message, "[lds] Stack offset 0 is out of bounds (FP = 1, SP = 1)");
+    let error = run("fun main\n  STS -1, 5\nend\n", "").result.unwrap_err();
+    assert_eq!(error.message, "[sts] Stack offset -1 is out of bounds (FP = 0, SP = 0)");
+}
+
+#[test]
+fn pushing_past_the_stack_size_is_a_catchable_overflow() {
+    let code = ".stack 3\n\nfun main\n  TRY overflow\n  MOV L0, 5\n  LOOP push_one\n  ENDTRY\n  DEPTH RET0\nend\n\nfun push_one\n  PUSH L0\nend\n\nfun overflow\n  MOV P0, RET0\n  JMP printline\nend\n";
+    let captured = run(code, "");
+    assert_eq!(captured.output, "StackOverflow\n");
+    assert_eq!(captured.result.unwrap(), 3);
+
+    let error = RunTime::new(".stack lots\n").unwrap_err();
+    assert_eq!(error.message, "Invalid stack size directive: .stack lots");
+}
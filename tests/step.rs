@@ -0,0 +1,60 @@
+use xasm::{ErrorKind, RunTime, SharedBuffer, Status};
+
+#[test]
+fn run_for_executes_a_bounded_number_of_steps() {
+    let code = "fun main\n  MOV R0, 0\n  MOV L0, 1000\n  LOOP count\nend\n\nfun count\n  INC R0\nend\n";
+    let mut runtime = RunTime::new(code).unwrap();
+    runtime.start(&[]).unwrap();
+    assert!(matches!(runtime.run_for(10), Status::Running));
+    assert!(matches!(runtime.register("R0"), Some(xasm::Data::Int(n)) if *n < 10));
+
+    let mut steps = 0;
+    while let Status::Running = runtime.run_for(100) {
+        steps += 1;
+    }
+    assert!(steps > 10);
+    assert!(matches!(runtime.status(), Status::Halted(0)));
+    assert!(matches!(runtime.register("R0"), Some(xasm::Data::Int(1000))));
+}
+
+#[test]
+fn input_builtins_wait_for_fed_input() {
+    let output = SharedBuffer::default();
+    let code = "fun main\n  MOV P0, \"number? \"\n  JMP input_int\n  MOV R0, RET0\n  JMP input\n  JNEOF clear\n  MOV RET0, R0\nend\n\nfun clear\n  MOV R0, 0\nend\n";
+    let mut runtime = RunTime::with_fed_input(code, output.clone(), SharedBuffer::default()).unwrap();
+    runtime.start(&[]).unwrap();
+    assert!(matches!(runtime.run_for(100), Status::WaitingForInput));
+    assert!(matches!(runtime.run_for(100), Status::WaitingForInput));
+    assert_eq!(output.contents(), "number? ");
+
+    runtime.feed_input("4");
+    assert!(matches!(runtime.run_for(100), Status::WaitingForInput));
+    assert_eq!(output.contents(), "number? "); //the prompt is only shown once while waiting
+    runtime.feed_input("2\n");
+    assert!(matches!(runtime.run_for(100), Status::WaitingForInput));
+    runtime.close_input();
+    assert!(matches!(runtime.run_for(100), Status::Halted(42)));
+    assert_eq!(output.contents(), "number? ");
+}
+
+#[test]
+fn uncaught_errors_end_the_program() {
+    let mut runtime = RunTime::with_fed_input("fun main\n  POP R0\nend\n", SharedBuffer::default(), SharedBuffer::default()).unwrap();
+    runtime.start(&[]).unwrap();
+    match runtime.run_for(10) {
+        Status::Errored(error) => {
+            assert_eq!(error.kind, ErrorKind::Stack);
+            assert_eq!(error.backtrace, vec!["main: POP R0".to_string()]);
+        }
+        status => panic!("expected an error, got {:?}", status),
+    }
+    assert!(matches!(runtime.step(), Status::Errored(_)));
+}
+
+#[test]
+fn recursion_is_not_limited_by_the_rust_stack() {
+    let code = "fun main\n  MOV R0, 0\n  JMP down\n  MOV P0, R0\n  JMP print\nend\n\nfun down\n  INC R0\n  CMP R0, 100000\n  JNE down\nend\n";
+    let captured = RunTime::run_with_input(code, "", &[]).unwrap();
+    assert_eq!(captured.result.unwrap(), 0);
+    assert_eq!(captured.output, "100000");
+}
@@ -0,0 +1,52 @@
+use xasm::{Data, ErrorKind, RunTime};
+
+mod common;
+use common::run;
+
+const STRUCTS: &str = "struct Point { x, y }\n\nstruct Line {\n  start\n  end\n}\n";
+
+#[test]
+fn fields_are_read_and_written_through_nested_paths() {
+    let code = format!("{}\nfun main\n  NEW R0, Point\n  SETF R0.x, 3\n  NEW R1, Line\n  SETF R1.start, R0\n  SETF R1.start.y, 4\n  GETF R2, R1.start.y\n  MOV P0, R2\n  JMP printline\n  MOV P0, R1\n  JMP printline\n  MOV P0, R0\n  JMP printline\nend\n", STRUCTS);
+    let captured = run(&code, "");
+    assert_eq!(captured.output, "4\nLine { start: Point { x: 3, y: 4 }, end: null }\nPoint { x: 3, y: null }\n");
+    assert_eq!(captured.result.unwrap(), 0);
+}
+
+#[test]
+fn field_errors_are_reported() {
+    let mut runtime = RunTime::new(STRUCTS).unwrap();
+    let error = runtime.execute("NEW R0, Circle").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Type);
+    assert_eq!(error.message, "[new] Attempted to create non-existant struct: Circle");
+
+    runtime.execute("NEW R0, Point").unwrap();
+    let error = runtime.execute("GETF R1, R0.z").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Field);
+    assert_eq!(error.message, "[getf] Struct Point has no field z");
+    let error = runtime.execute("SETF R0.z, 1").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Field);
+    assert_eq!(error.message, "[setf] Struct Point has no field z");
+    let error = runtime.execute("GETF R1, R0").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Field);
+    assert_eq!(error.message, "[getf] Expected register.field, got: R0");
+
+    let error = runtime.execute("GETF R1, R0.x.y").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Type);
+    assert_eq!(error.message, "[getf] Attempted to get field y of non-struct value: R0.x.y");
+    runtime.execute("MOV R1, 5").unwrap();
+    let error = runtime.execute("SETF R1.x, 1").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Type);
+    assert_eq!(error.message, "[setf] Attempted to set field x of non-struct value: R1.x");
+    let error = runtime.execute("SETF nothing.x, 1").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Register);
+    assert!(matches!(runtime.register("R1"), Some(Data::Int(5))));
+}
+
+#[test]
+fn invalid_struct_declarations_are_rejected() {
+    let error = RunTime::new("struct Point x, y\n").unwrap_err();
+    assert_eq!(error.message, "Invalid struct declaration: struct Point x, y");
+    let error = RunTime::new("struct { x }\n").unwrap_err();
+    assert_eq!(error.message, "Invalid struct declaration: struct { x }");
+}
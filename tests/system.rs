@@ -0,0 +1,76 @@
+use xasm::{Data, ErrorKind, RunTime};
+
+#[test]
+fn environment_variables_can_be_set_and_read() {
+    let mut runtime = RunTime::default();
+    runtime.execute("MOV P0, \"XASM_TEST_SYSTEM_VARIABLE\"").unwrap();
+    runtime.execute("JMP getenv").unwrap();
+    assert!(matches!(runtime.register("RET0"), Some(Data::Null)));
+
+    runtime.execute("MOV P1, 42").unwrap();
+    runtime.execute("JMP setenv").unwrap();
+    runtime.execute("JMP getenv").unwrap();
+    assert!(matches!(runtime.register("RET0"), Some(Data::String(value)) if value == "42"));
+
+    runtime.execute("MOV P0, \"A=B\"").unwrap();
+    let error = runtime.execute("JMP setenv").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Argument);
+    assert_eq!(error.message, "[setenv] Invalid environment variable: A=B");
+}
+
+#[test]
+fn time_and_date_agree() {
+    let mut runtime = RunTime::default();
+    runtime.execute("JMP time").unwrap();
+    let (Some(Data::Int(seconds)), Some(Data::Int(millis))) = (runtime.register("RET0").cloned(), runtime.register("RET1").cloned()) else {
+        panic!("time should return integers");
+    };
+    assert_eq!(millis / 1000, seconds);
+
+    runtime.execute("MOV P0, \"%s %%\"").unwrap();
+    runtime.execute("JMP date").unwrap();
+    let Some(Data::String(date)) = runtime.register("RET0") else {
+        panic!("date should return a string");
+    };
+    let (timestamp, percent) = date.split_once(' ').unwrap();
+    assert!((seconds..=seconds + 1).contains(&timestamp.parse::<i64>().unwrap()));
+    assert_eq!(percent, "%");
+
+    runtime.execute("MOV P0, \"\"").unwrap();
+    runtime.execute("JMP date").unwrap();
+    let Some(Data::String(date)) = runtime.register("RET0") else {
+        panic!("date should return a string");
+    };
+    assert_eq!(date.len(), "2024-01-31 12:00:00".len());
+    assert_eq!((&date[4..5], &date[10..11], &date[13..14]), ("-", " ", ":"));
+
+    runtime.execute("MOV P0, 5").unwrap();
+    assert_eq!(runtime.execute("JMP date").unwrap_err().kind, ErrorKind::Type);
+}
+
+#[test]
+fn the_clock_measures_sleeps() {
+    let mut runtime = RunTime::default();
+    runtime.execute("JMP clock").unwrap();
+    runtime.execute("MOV R0, RET0").unwrap();
+    runtime.execute("MOV P0, 20").unwrap();
+    runtime.execute("JMP sleep").unwrap();
+    runtime.execute("JMP clock").unwrap();
+    runtime.execute("SUB RET0, R0").unwrap();
+    assert!(matches!(runtime.register("RET0"), Some(Data::Float(elapsed)) if *elapsed >= 20.0));
+
+    runtime.execute("MOV P0, -1").unwrap();
+    let error = runtime.execute("JMP sleep").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Argument);
+    assert_eq!(error.message, "[sleep] Attempted to sleep for a negative duration: -1");
+    runtime.execute("MOV P0, 1.5").unwrap();
+    assert_eq!(runtime.execute("JMP sleep").unwrap_err().kind, ErrorKind::Type);
+}
+
+#[test]
+fn cwd_is_the_working_directory() {
+    let mut runtime = RunTime::default();
+    runtime.execute("JMP cwd").unwrap();
+    let dir = std::env::current_dir().unwrap();
+    assert!(matches!(runtime.register("RET0"), Some(Data::String(cwd)) if *cwd == dir.to_string_lossy()));
+}
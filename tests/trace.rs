@@ -0,0 +1,48 @@
+use std::io::Cursor;
+
+use xasm::{RunTime, SharedBuffer, Tracer};
+
+const PROGRAM: &str = "fun double n -> result
+  MOV result, n
+  ADD result, n
+end
+
+fun main
+  CALL double 4
+  CMP RET0, 8
+  MOV RET0, 0
+end
+";
+
+fn trace(only: Option<&str>) -> String {
+    let mut runtime = RunTime::with_streams(PROGRAM, Cursor::new(""), SharedBuffer::default(), SharedBuffer::default()).unwrap();
+    let output = SharedBuffer::default();
+    let mut tracer = Tracer::new(output.clone());
+    if let Some(function) = only {
+        tracer.only(function);
+    }
+    runtime.start(&[]).unwrap();
+    assert_eq!(tracer.run(&mut runtime).unwrap(), 0);
+    output.contents()
+}
+
+#[test]
+fn every_instruction_is_traced_with_its_changes() {
+    assert_eq!(trace(None), "\
+main (line 7): CALL double 4 | n: undefined → 4, result: undefined → null
+  double (line 2): MOV result, n | result: null → 4
+  double (line 3): ADD result, n | result: 4 → 8
+  return from double | RET0: null → 8, n: 4 → undefined, result: 8 → undefined
+main (line 8): CMP RET0, 8 | equal: false → true
+main (line 9): MOV RET0, 0 | RET0: 8 → 0
+");
+}
+
+#[test]
+fn only_the_given_functions_are_traced() {
+    assert_eq!(trace(Some("main")), "\
+main (line 7): CALL double 4 | n: undefined → 4, result: undefined → null
+main (line 8): CMP RET0, 8 | equal: false → true
+main (line 9): MOV RET0, 0 | RET0: 8 → 0
+");
+}